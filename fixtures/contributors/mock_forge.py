@@ -0,0 +1,36 @@
+#!/usr/bin/env python3
+"""Serves `contributors.json` like the GitHub contributors endpoint, paginated with `Link` headers.
+
+Usage: ./mock_forge.py [port]
+Then run the API with CONTRIBUTORS_URL=http://localhost:<port>/repos/owner/repo/contributors
+"""
+
+import json
+import sys
+from http.server import BaseHTTPRequestHandler, HTTPServer
+from pathlib import Path
+from urllib.parse import parse_qs, urlparse
+
+CONTRIBUTORS = json.loads((Path(__file__).parent / "contributors.json").read_text())
+PAGE_SIZE = 2
+
+
+class Handler(BaseHTTPRequestHandler):
+    def do_GET(self):
+        url = urlparse(self.path)
+        page = int(parse_qs(url.query).get("page", ["1"])[0])
+        start = (page - 1) * PAGE_SIZE
+        body = json.dumps(CONTRIBUTORS[start : start + PAGE_SIZE]).encode()
+
+        self.send_response(200)
+        self.send_header("Content-Type", "application/json")
+        if start + PAGE_SIZE < len(CONTRIBUTORS):
+            next_url = f"http://{self.headers['Host']}{url.path}?page={page + 1}"
+            self.send_header("Link", f'<{next_url}>; rel="next"')
+        self.end_headers()
+        self.wfile.write(body)
+
+
+if __name__ == "__main__":
+    port = int(sys.argv[1]) if len(sys.argv) > 1 else 8000
+    HTTPServer(("127.0.0.1", port), Handler).serve_forever()
@@ -0,0 +1,14 @@
+#!/bin/sh
+# Signs a fixture payload with `DONATIONS_WEBHOOK_SECRET` and sends it to the donation webhook.
+#
+# Usage: DONATIONS_WEBHOOK_SECRET=... ./send.sh <fixture.json> [url]
+set -eu
+
+url="${2:-http://localhost:3333/v2/integrations/donations}"
+signature=$(openssl dgst -sha256 -hmac "$DONATIONS_WEBHOOK_SECRET" -hex < "$1" | sed 's/^.* //')
+
+curl -sS -X POST "$url" \
+  -H "Content-Type: application/json" \
+  -H "X-Signature-256: sha256=$signature" \
+  --data-binary "@$1"
+echo
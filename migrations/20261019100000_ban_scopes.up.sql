@@ -0,0 +1 @@
+ALTER TABLE bans ADD COLUMN scope TEXT DEFAULT 'global' NOT NULL;
@@ -0,0 +1,2 @@
+-- Badge types that were normalized to `custom` cannot be restored.
+SELECT 1;
@@ -0,0 +1,4 @@
+UPDATE badges SET badge_type = lower(badge_type);
+
+UPDATE badges SET badge_type = 'custom'
+WHERE badge_type NOT IN ('donor', 'contributor', 'staff', 'event', 'custom');
@@ -0,0 +1,25 @@
+DROP VIEW resolved_badges;
+
+CREATE TABLE badges_old (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  user_id TEXT NOT NULL,
+  badge TEXT NOT NULL,
+  tooltip TEXT NOT NULL,
+  badge_type TEXT DEFAULT 'donor' NOT NULL
+);
+
+INSERT INTO badges_old (id, user_id, badge, tooltip, badge_type)
+SELECT
+  badges.id,
+  badges.user_id,
+  COALESCE(badges.badge, badge_definitions.badge),
+  COALESCE(badges.tooltip, badge_definitions.tooltip),
+  COALESCE(badges.badge_type, badge_definitions.badge_type)
+FROM badges
+LEFT JOIN badge_definitions ON badge_definitions.id = badges.definition_id;
+
+DROP TABLE badges;
+
+ALTER TABLE badges_old RENAME TO badges;
+
+DROP TABLE badge_definitions;
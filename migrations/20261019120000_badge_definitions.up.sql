@@ -0,0 +1,59 @@
+CREATE TABLE badge_definitions (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  name TEXT NOT NULL,
+  badge TEXT NOT NULL,
+  tooltip TEXT NOT NULL,
+  badge_type TEXT DEFAULT 'donor' NOT NULL
+);
+
+-- Badges that are shared by multiple users become definitions
+INSERT INTO badge_definitions (name, badge, tooltip, badge_type)
+SELECT tooltip, badge, tooltip, badge_type
+FROM badges
+GROUP BY badge, tooltip, badge_type
+HAVING COUNT(*) > 1;
+
+CREATE TABLE badges_new (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  user_id TEXT NOT NULL,
+  definition_id INTEGER REFERENCES badge_definitions (id) ON DELETE CASCADE,
+  badge TEXT,
+  tooltip TEXT,
+  badge_type TEXT,
+  CHECK (
+    definition_id IS NOT NULL
+    OR (badge IS NOT NULL AND tooltip IS NOT NULL AND badge_type IS NOT NULL)
+  )
+);
+
+INSERT INTO badges_new (id, user_id, definition_id, badge, tooltip, badge_type)
+SELECT
+  badges.id,
+  badges.user_id,
+  badge_definitions.id,
+  CASE WHEN badge_definitions.id IS NULL THEN badges.badge END,
+  CASE WHEN badge_definitions.id IS NULL THEN badges.tooltip END,
+  CASE WHEN badge_definitions.id IS NULL THEN badges.badge_type END
+FROM badges
+LEFT JOIN badge_definitions
+  ON badge_definitions.badge = badges.badge
+  AND badge_definitions.tooltip = badges.tooltip
+  AND badge_definitions.badge_type = badges.badge_type;
+
+DROP TABLE badges;
+
+ALTER TABLE badges_new RENAME TO badges;
+
+CREATE INDEX badges_user_id ON badges (user_id);
+
+-- Badges with their per-user overrides applied on top of their definition
+CREATE VIEW resolved_badges AS
+SELECT
+  badges.id,
+  badges.user_id,
+  badges.definition_id,
+  COALESCE(badges.badge, badge_definitions.badge) AS badge,
+  COALESCE(badges.tooltip, badge_definitions.tooltip) AS tooltip,
+  COALESCE(badges.badge_type, badge_definitions.badge_type) AS badge_type
+FROM badges
+LEFT JOIN badge_definitions ON badge_definitions.id = badges.definition_id;
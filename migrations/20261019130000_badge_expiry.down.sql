@@ -0,0 +1,20 @@
+DROP VIEW resolved_badges;
+
+DROP TABLE archived_badges;
+
+DROP INDEX badges_expires_at;
+
+ALTER TABLE badges DROP COLUMN expires_at;
+
+ALTER TABLE badges DROP COLUMN granted_at;
+
+CREATE VIEW resolved_badges AS
+SELECT
+  badges.id,
+  badges.user_id,
+  badges.definition_id,
+  COALESCE(badges.badge, badge_definitions.badge) AS badge,
+  COALESCE(badges.tooltip, badge_definitions.tooltip) AS tooltip,
+  COALESCE(badges.badge_type, badge_definitions.badge_type) AS badge_type
+FROM badges
+LEFT JOIN badge_definitions ON badge_definitions.id = badges.definition_id;
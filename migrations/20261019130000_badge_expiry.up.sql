@@ -0,0 +1,53 @@
+DROP VIEW resolved_badges;
+
+CREATE TABLE badges_new (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  user_id TEXT NOT NULL,
+  definition_id INTEGER REFERENCES badge_definitions (id) ON DELETE CASCADE,
+  badge TEXT,
+  tooltip TEXT,
+  badge_type TEXT,
+  granted_at DATETIME DEFAULT CURRENT_TIMESTAMP NOT NULL,
+  expires_at DATETIME,
+  CHECK (
+    definition_id IS NOT NULL
+    OR (badge IS NOT NULL AND tooltip IS NOT NULL AND badge_type IS NOT NULL)
+  )
+);
+
+INSERT INTO badges_new (id, user_id, definition_id, badge, tooltip, badge_type)
+SELECT id, user_id, definition_id, badge, tooltip, badge_type FROM badges;
+
+DROP TABLE badges;
+
+ALTER TABLE badges_new RENAME TO badges;
+
+CREATE INDEX badges_user_id ON badges (user_id);
+
+CREATE INDEX badges_expires_at ON badges (expires_at);
+
+-- Expired badges are moved here by the expiry sweep
+CREATE TABLE archived_badges (
+  id INTEGER PRIMARY KEY NOT NULL,
+  user_id TEXT NOT NULL,
+  definition_id INTEGER,
+  badge TEXT,
+  tooltip TEXT,
+  badge_type TEXT,
+  granted_at DATETIME NOT NULL,
+  expires_at DATETIME,
+  archived_at DATETIME DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
+
+CREATE VIEW resolved_badges AS
+SELECT
+  badges.id,
+  badges.user_id,
+  badges.definition_id,
+  COALESCE(badges.badge, badge_definitions.badge) AS badge,
+  COALESCE(badges.tooltip, badge_definitions.tooltip) AS tooltip,
+  COALESCE(badges.badge_type, badge_definitions.badge_type) AS badge_type,
+  badges.granted_at,
+  badges.expires_at
+FROM badges
+LEFT JOIN badge_definitions ON badge_definitions.id = badges.definition_id;
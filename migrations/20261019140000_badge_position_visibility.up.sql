@@ -0,0 +1,20 @@
+ALTER TABLE badges ADD COLUMN position INTEGER DEFAULT 0 NOT NULL;
+
+ALTER TABLE badges ADD COLUMN hidden BOOLEAN DEFAULT FALSE NOT NULL;
+
+DROP VIEW resolved_badges;
+
+CREATE VIEW resolved_badges AS
+SELECT
+  badges.id,
+  badges.user_id,
+  badges.definition_id,
+  COALESCE(badges.badge, badge_definitions.badge) AS badge,
+  COALESCE(badges.tooltip, badge_definitions.tooltip) AS tooltip,
+  COALESCE(badges.badge_type, badge_definitions.badge_type) AS badge_type,
+  badges.granted_at,
+  badges.expires_at,
+  badges.position,
+  badges.hidden
+FROM badges
+LEFT JOIN badge_definitions ON badge_definitions.id = badges.definition_id;
@@ -0,0 +1,9 @@
+DROP TRIGGER badge_definitions_update_revision;
+
+DROP TRIGGER badges_delete_revision;
+
+DROP TRIGGER badges_update_revision;
+
+DROP TRIGGER badges_insert_revision;
+
+DROP TABLE revisions;
@@ -0,0 +1,27 @@
+-- Revision counters that change on every write to the tables they track
+CREATE TABLE revisions (
+  name TEXT PRIMARY KEY NOT NULL,
+  revision INTEGER DEFAULT 0 NOT NULL
+);
+
+INSERT INTO revisions (name) VALUES ('badges');
+
+CREATE TRIGGER badges_insert_revision AFTER INSERT ON badges
+BEGIN
+  UPDATE revisions SET revision = revision + 1 WHERE name = 'badges';
+END;
+
+CREATE TRIGGER badges_update_revision AFTER UPDATE ON badges
+BEGIN
+  UPDATE revisions SET revision = revision + 1 WHERE name = 'badges';
+END;
+
+CREATE TRIGGER badges_delete_revision AFTER DELETE ON badges
+BEGIN
+  UPDATE revisions SET revision = revision + 1 WHERE name = 'badges';
+END;
+
+CREATE TRIGGER badge_definitions_update_revision AFTER UPDATE ON badge_definitions
+BEGIN
+  UPDATE revisions SET revision = revision + 1 WHERE name = 'badges';
+END;
@@ -0,0 +1,52 @@
+DROP VIEW resolved_badges;
+
+CREATE VIEW resolved_badges AS
+SELECT
+  badges.id,
+  badges.user_id,
+  badges.definition_id,
+  COALESCE(badges.badge, badge_definitions.badge) AS badge,
+  COALESCE(badges.tooltip, badge_definitions.tooltip) AS tooltip,
+  COALESCE(badges.badge_type, badge_definitions.badge_type) AS badge_type,
+  badges.granted_at,
+  badges.expires_at,
+  badges.position,
+  badges.hidden
+FROM badges
+LEFT JOIN badge_definitions ON badge_definitions.id = badges.definition_id;
+
+DROP TRIGGER badge_definitions_update_revision;
+
+DROP TRIGGER badges_delete_revision;
+
+DROP TRIGGER badges_update_revision;
+
+DROP TRIGGER badges_insert_revision;
+
+CREATE TRIGGER badges_insert_revision AFTER INSERT ON badges
+BEGIN
+  UPDATE revisions SET revision = revision + 1 WHERE name = 'badges';
+END;
+
+CREATE TRIGGER badges_update_revision AFTER UPDATE ON badges
+BEGIN
+  UPDATE revisions SET revision = revision + 1 WHERE name = 'badges';
+END;
+
+CREATE TRIGGER badges_delete_revision AFTER DELETE ON badges
+BEGIN
+  UPDATE revisions SET revision = revision + 1 WHERE name = 'badges';
+END;
+
+CREATE TRIGGER badge_definitions_update_revision AFTER UPDATE ON badge_definitions
+BEGIN
+  UPDATE revisions SET revision = revision + 1 WHERE name = 'badges';
+END;
+
+DROP TABLE badge_tombstones;
+
+DROP INDEX badges_revision;
+
+ALTER TABLE badges DROP COLUMN created_revision;
+
+ALTER TABLE badges DROP COLUMN revision;
@@ -0,0 +1,76 @@
+ALTER TABLE badges ADD COLUMN revision INTEGER DEFAULT 0 NOT NULL;
+
+ALTER TABLE badges ADD COLUMN created_revision INTEGER DEFAULT 0 NOT NULL;
+
+CREATE INDEX badges_revision ON badges (revision);
+
+-- Deleted badges, so clients syncing from the change feed can remove them
+CREATE TABLE badge_tombstones (
+  id INTEGER PRIMARY KEY NOT NULL,
+  user_id TEXT NOT NULL,
+  revision INTEGER NOT NULL
+);
+
+CREATE INDEX badge_tombstones_revision ON badge_tombstones (revision);
+
+DROP TRIGGER badge_definitions_update_revision;
+
+DROP TRIGGER badges_delete_revision;
+
+DROP TRIGGER badges_update_revision;
+
+DROP TRIGGER badges_insert_revision;
+
+CREATE TRIGGER badges_insert_revision AFTER INSERT ON badges
+BEGIN
+  UPDATE revisions SET revision = revision + 1 WHERE name = 'badges';
+  UPDATE badges
+  SET
+    revision = (SELECT revision FROM revisions WHERE name = 'badges'),
+    created_revision = (SELECT revision FROM revisions WHERE name = 'badges')
+  WHERE id = NEW.id;
+END;
+
+-- Skips the updates of the revision itself made by these triggers
+CREATE TRIGGER badges_update_revision AFTER UPDATE ON badges
+WHEN NEW.revision IS OLD.revision
+BEGIN
+  UPDATE revisions SET revision = revision + 1 WHERE name = 'badges';
+  UPDATE badges
+  SET revision = (SELECT revision FROM revisions WHERE name = 'badges')
+  WHERE id = NEW.id;
+END;
+
+CREATE TRIGGER badges_delete_revision AFTER DELETE ON badges
+BEGIN
+  UPDATE revisions SET revision = revision + 1 WHERE name = 'badges';
+  INSERT OR REPLACE INTO badge_tombstones (id, user_id, revision)
+  VALUES (OLD.id, OLD.user_id, (SELECT revision FROM revisions WHERE name = 'badges'));
+END;
+
+CREATE TRIGGER badge_definitions_update_revision AFTER UPDATE ON badge_definitions
+BEGIN
+  UPDATE revisions SET revision = revision + 1 WHERE name = 'badges';
+  UPDATE badges
+  SET revision = (SELECT revision FROM revisions WHERE name = 'badges')
+  WHERE definition_id = NEW.id;
+END;
+
+DROP VIEW resolved_badges;
+
+CREATE VIEW resolved_badges AS
+SELECT
+  badges.id,
+  badges.user_id,
+  badges.definition_id,
+  COALESCE(badges.badge, badge_definitions.badge) AS badge,
+  COALESCE(badges.tooltip, badge_definitions.tooltip) AS tooltip,
+  COALESCE(badges.badge_type, badge_definitions.badge_type) AS badge_type,
+  badges.granted_at,
+  badges.expires_at,
+  badges.position,
+  badges.hidden,
+  badges.revision,
+  badges.created_revision
+FROM badges
+LEFT JOIN badge_definitions ON badge_definitions.id = badges.definition_id;
@@ -0,0 +1 @@
+DROP TABLE badge_customizations;
@@ -0,0 +1,14 @@
+-- Changes badge holders requested to their own badges, reviewed by moderators
+CREATE TABLE badge_customizations (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  badge_id INTEGER NOT NULL REFERENCES badges (id) ON DELETE CASCADE,
+  user_id TEXT NOT NULL,
+  tooltip TEXT,
+  badge TEXT,
+  status TEXT DEFAULT 'pending' NOT NULL,
+  created_at DATETIME DEFAULT CURRENT_TIMESTAMP NOT NULL,
+  reviewed_at DATETIME,
+  reviewed_by TEXT
+);
+
+CREATE INDEX badge_customizations_status ON badge_customizations (status);
@@ -0,0 +1,3 @@
+DROP TABLE badge_claim_redemptions;
+
+DROP TABLE badge_claim_codes;
@@ -0,0 +1,18 @@
+-- Codes that grant a badge definition to whoever redeems them
+CREATE TABLE badge_claim_codes (
+  code TEXT PRIMARY KEY NOT NULL,
+  definition_id INTEGER NOT NULL REFERENCES badge_definitions (id) ON DELETE CASCADE,
+  max_uses INTEGER,
+  uses INTEGER DEFAULT 0 NOT NULL,
+  expires_at DATETIME,
+  created_at DATETIME DEFAULT CURRENT_TIMESTAMP NOT NULL,
+  created_by TEXT NOT NULL
+);
+
+CREATE TABLE badge_claim_redemptions (
+  code TEXT NOT NULL REFERENCES badge_claim_codes (code) ON DELETE CASCADE,
+  user_id TEXT NOT NULL,
+  badge_id INTEGER NOT NULL,
+  redeemed_at DATETIME DEFAULT CURRENT_TIMESTAMP NOT NULL,
+  PRIMARY KEY (code, user_id)
+);
@@ -0,0 +1,3 @@
+DROP TABLE badge_definition_tooltip_translations;
+
+DROP TABLE badge_tooltip_translations;
@@ -0,0 +1,50 @@
+CREATE TABLE badge_tooltip_translations (
+  badge_id INTEGER NOT NULL REFERENCES badges (id) ON DELETE CASCADE,
+  locale TEXT NOT NULL,
+  tooltip TEXT NOT NULL,
+  PRIMARY KEY (badge_id, locale)
+);
+
+CREATE TABLE badge_definition_tooltip_translations (
+  definition_id INTEGER NOT NULL REFERENCES badge_definitions (id) ON DELETE CASCADE,
+  locale TEXT NOT NULL,
+  tooltip TEXT NOT NULL,
+  PRIMARY KEY (definition_id, locale)
+);
+
+-- Translations change the badges they belong to, so they bump their revision as well
+CREATE TRIGGER badge_tooltip_translations_insert_revision
+AFTER INSERT ON badge_tooltip_translations
+BEGIN
+  UPDATE badges SET tooltip = tooltip WHERE id = NEW.badge_id;
+END;
+
+CREATE TRIGGER badge_tooltip_translations_update_revision
+AFTER UPDATE ON badge_tooltip_translations
+BEGIN
+  UPDATE badges SET tooltip = tooltip WHERE id = NEW.badge_id;
+END;
+
+CREATE TRIGGER badge_tooltip_translations_delete_revision
+AFTER DELETE ON badge_tooltip_translations
+BEGIN
+  UPDATE badges SET tooltip = tooltip WHERE id = OLD.badge_id;
+END;
+
+CREATE TRIGGER badge_definition_tooltip_translations_insert_revision
+AFTER INSERT ON badge_definition_tooltip_translations
+BEGIN
+  UPDATE badges SET tooltip = tooltip WHERE definition_id = NEW.definition_id;
+END;
+
+CREATE TRIGGER badge_definition_tooltip_translations_update_revision
+AFTER UPDATE ON badge_definition_tooltip_translations
+BEGIN
+  UPDATE badges SET tooltip = tooltip WHERE definition_id = NEW.definition_id;
+END;
+
+CREATE TRIGGER badge_definition_tooltip_translations_delete_revision
+AFTER DELETE ON badge_definition_tooltip_translations
+BEGIN
+  UPDATE badges SET tooltip = tooltip WHERE definition_id = OLD.definition_id;
+END;
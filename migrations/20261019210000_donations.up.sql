@@ -0,0 +1,13 @@
+-- Donations received through the donation webhook, and the donor badge they granted
+CREATE TABLE donations (
+  id TEXT PRIMARY KEY NOT NULL,
+  user_id TEXT NOT NULL,
+  badge_id INTEGER REFERENCES badges (id) ON DELETE SET NULL,
+  expires_at DATETIME NOT NULL,
+  created_at DATETIME DEFAULT CURRENT_TIMESTAMP NOT NULL,
+  revoked_at DATETIME
+);
+
+CREATE INDEX donations_user_id ON donations (user_id);
+
+CREATE INDEX donations_badge_id ON donations (badge_id);
@@ -0,0 +1 @@
+DROP TABLE contributor_links;
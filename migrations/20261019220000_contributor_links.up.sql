@@ -0,0 +1,6 @@
+-- Links accounts on the Git forge to Discord accounts, for the contributor badge sync
+CREATE TABLE contributor_links (
+  login TEXT PRIMARY KEY NOT NULL COLLATE NOCASE,
+  user_id TEXT NOT NULL,
+  created_at DATETIME DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
@@ -0,0 +1,14 @@
+CREATE TABLE themes_old (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  name TEXT NOT NULL,
+  description TEXT,
+  author TEXT,
+  css TEXT NOT NULL
+);
+
+INSERT INTO themes_old (id, name, description, author, css)
+SELECT id, name, description, COALESCE(author_id, legacy_author), css FROM themes;
+
+DROP TABLE themes;
+
+ALTER TABLE themes_old RENAME TO themes;
@@ -0,0 +1,29 @@
+-- Theme authors become users instead of free text. Authors that are not a known user are kept in
+-- `legacy_author`, and those themes can only be edited with the `ManageThemes` permission.
+CREATE TABLE themes_new (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  name TEXT NOT NULL,
+  description TEXT,
+  author_id TEXT REFERENCES users (id) ON DELETE SET NULL,
+  legacy_author TEXT,
+  css TEXT NOT NULL,
+  created_at DATETIME DEFAULT CURRENT_TIMESTAMP NOT NULL,
+  updated_at DATETIME DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
+
+INSERT INTO themes_new (id, name, description, author_id, legacy_author, css)
+SELECT
+  themes.id,
+  themes.name,
+  themes.description,
+  users.id,
+  CASE WHEN users.id IS NULL THEN themes.author END,
+  themes.css
+FROM themes
+LEFT JOIN users ON users.id = themes.author;
+
+DROP TABLE themes;
+
+ALTER TABLE themes_new RENAME TO themes;
+
+CREATE INDEX themes_author_id ON themes (author_id);
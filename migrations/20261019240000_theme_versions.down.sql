@@ -0,0 +1,7 @@
+DROP TRIGGER theme_versions_immutable;
+
+DROP TABLE theme_versions;
+
+ALTER TABLE themes DROP COLUMN min_client_version;
+
+ALTER TABLE themes DROP COLUMN version;
@@ -0,0 +1,26 @@
+ALTER TABLE themes ADD COLUMN version TEXT DEFAULT '1.0.0' NOT NULL;
+
+ALTER TABLE themes ADD COLUMN min_client_version TEXT;
+
+-- Every published state of a theme. Versions are never changed, so they can be pinned by users.
+CREATE TABLE theme_versions (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  theme_id INTEGER NOT NULL REFERENCES themes (id) ON DELETE CASCADE,
+  version TEXT NOT NULL,
+  name TEXT NOT NULL,
+  description TEXT,
+  css TEXT NOT NULL,
+  changelog TEXT,
+  min_client_version TEXT,
+  created_at DATETIME DEFAULT CURRENT_TIMESTAMP NOT NULL,
+  UNIQUE (theme_id, version)
+);
+
+INSERT INTO theme_versions (theme_id, version, name, description, css, created_at)
+SELECT id, version, name, description, css, updated_at FROM themes;
+
+CREATE TRIGGER theme_versions_immutable
+BEFORE UPDATE ON theme_versions
+BEGIN
+  SELECT RAISE(ABORT, 'theme versions are immutable');
+END;
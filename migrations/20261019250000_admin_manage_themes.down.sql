@@ -0,0 +1 @@
+UPDATE users SET permissions = permissions & ~16 WHERE permissions & 7 = 7;
@@ -0,0 +1,2 @@
+-- `Admin` now includes `ManageThemes`, so existing admins are granted it to keep matching `Admin`
+UPDATE users SET permissions = permissions | 16 WHERE permissions & 7 = 7;
@@ -0,0 +1,7 @@
+-- Bans with several scopes cannot be represented anymore and become global
+ALTER TABLE bans ADD COLUMN scopes TEXT DEFAULT 'global' NOT NULL;
+
+UPDATE bans SET scopes = CASE scope WHEN 2 THEN 'socket' WHEN 4 THEN 'badges' ELSE 'global' END;
+
+ALTER TABLE bans DROP COLUMN scope;
+ALTER TABLE bans RENAME COLUMN scopes TO scope;
@@ -0,0 +1,7 @@
+-- Ban scopes become a bitmask, so a single ban can restrict several scopes
+ALTER TABLE bans ADD COLUMN scopes INTEGER DEFAULT 1 NOT NULL;
+
+UPDATE bans SET scopes = CASE scope WHEN 'socket' THEN 2 WHEN 'badges' THEN 4 ELSE 1 END;
+
+ALTER TABLE bans DROP COLUMN scope;
+ALTER TABLE bans RENAME COLUMN scopes TO scope;
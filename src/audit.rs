@@ -0,0 +1,28 @@
+use serde::Serialize;
+use sqlx::SqliteConnection;
+
+use crate::error::Error;
+
+/// Records an administrative action in the audit log.
+///
+/// Call this within the transaction of the action itself, so the entry is only kept if the action
+/// is.
+pub async fn record(
+    conn: &mut SqliteConnection,
+    actor_id: &str,
+    action: &str,
+    details: &impl Serialize,
+) -> Result<(), Error> {
+    let details = serde_json::to_string(details).map_err(|error| Error::Other(error.into()))?;
+
+    sqlx::query!(
+        "INSERT INTO audit_log (actor_id, action, details) VALUES (?, ?, ?)",
+        actor_id,
+        action,
+        details
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
@@ -42,8 +42,12 @@ pub enum Permissions {
     ManageBans,
     ManageBadges,
     ManageUsers,
+    ManageThemes,
 
-    Admin = Self::ListBans.bits | Self::ManageBans.bits | Self::ManageBadges.bits,
+    Admin = Self::ListBans.bits
+        | Self::ManageBans.bits
+        | Self::ManageBadges.bits
+        | Self::ManageThemes.bits,
     Owner = Self::Admin.bits | Self::ManageUsers.bits,
 }
 
@@ -0,0 +1,158 @@
+use std::{
+    collections::HashMap,
+    fmt::Write,
+    hash::Hash,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, RwLock,
+    },
+};
+
+use axum::body::Bytes;
+use axum_extra::headers::ETag;
+use chrono::{DateTime, Utc};
+
+use crate::{formats::BadgeListFormat, models::BadgeType};
+
+/// The maximum amount of entries a single cache holds before it is cleared.
+const MAX_ENTRIES: usize = 10_000;
+
+/// A cache for the responses of the public read endpoints.
+#[derive(Default)]
+pub struct Cache {
+    pub badges: CacheMap<BadgeListKey, CachedBadgeList>,
+    pub bans: CacheMap<String, Option<Bytes>>,
+    /// The locales that have at least one tooltip translation.
+    pub translation_locales: CacheMap<(), Arc<[String]>>,
+}
+
+impl Cache {
+    /// Renders the hit and miss counters of all caches in the Prometheus text format.
+    pub fn render_metrics(&self) -> String {
+        let mut output = String::new();
+
+        for (name, cache) in [
+            ("badges", &self.badges.metrics),
+            ("bans", &self.bans.metrics),
+            ("translation_locales", &self.translation_locales.metrics),
+        ] {
+            writeln!(
+                output,
+                "cache_hits_total{{cache=\"{name}\"}} {}",
+                cache.hits.load(Ordering::Relaxed)
+            )
+            .ok();
+            writeln!(
+                output,
+                "cache_misses_total{{cache=\"{name}\"}} {}",
+                cache.misses.load(Ordering::Relaxed)
+            )
+            .ok();
+        }
+
+        output
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct BadgeListKey {
+    pub format: BadgeListFormat,
+    pub badge_type: Option<BadgeType>,
+    pub locales: Vec<String>,
+}
+
+#[derive(Clone)]
+pub struct CachedBadgeList {
+    pub etag: ETag,
+    pub body: Bytes,
+    /// When the first badge in the list expires, after which the list must be rebuilt.
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl CachedBadgeList {
+    pub fn is_fresh(&self) -> bool {
+        self.expires_at
+            .is_none_or(|expires_at| expires_at > Utc::now())
+    }
+}
+
+#[derive(Default)]
+pub struct CacheMetrics {
+    pub hits: AtomicU64,
+    pub misses: AtomicU64,
+}
+
+struct CacheEntries<K, V> {
+    generation: u64,
+    entries: HashMap<K, V>,
+}
+
+/// A map of cached values that is invalidated by write handlers.
+///
+/// Every invalidation bumps the generation of the cache. Values read from the database before an
+/// invalidation are not inserted, so a slow read can never overwrite a newer write.
+pub struct CacheMap<K, V> {
+    inner: RwLock<CacheEntries<K, V>>,
+    pub metrics: CacheMetrics,
+}
+
+impl<K, V> Default for CacheMap<K, V> {
+    fn default() -> Self {
+        Self {
+            inner: RwLock::new(CacheEntries {
+                generation: 0,
+                entries: HashMap::new(),
+            }),
+            metrics: CacheMetrics::default(),
+        }
+    }
+}
+
+impl<K, V> CacheMap<K, V>
+where
+    K: Eq + Hash,
+    V: Clone,
+{
+    pub fn get(&self, key: &K) -> Option<V> {
+        let value = self.inner.read().unwrap().entries.get(key).cloned();
+
+        let counter = match value {
+            Some(_) => &self.metrics.hits,
+            None => &self.metrics.misses,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+
+        value
+    }
+
+    /// The current generation, which must be read before reading the value from the database.
+    pub fn generation(&self) -> u64 {
+        self.inner.read().unwrap().generation
+    }
+
+    pub fn insert(&self, generation: u64, key: K, value: V) {
+        let mut inner = self.inner.write().unwrap();
+
+        if inner.generation != generation {
+            return;
+        }
+
+        if inner.entries.len() >= MAX_ENTRIES {
+            inner.entries.clear();
+        }
+
+        inner.entries.insert(key, value);
+    }
+
+    pub fn invalidate(&self) {
+        let mut inner = self.inner.write().unwrap();
+        inner.generation += 1;
+        inner.entries.clear();
+    }
+
+    pub fn invalidate_key(&self, key: &K) {
+        let mut inner = self.inner.write().unwrap();
+        inner.generation += 1;
+        inner.entries.remove(key);
+    }
+}
@@ -0,0 +1,55 @@
+use std::sync::Arc;
+
+use axum::{extract::State, Json};
+use axum_extra::extract::Query;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    auth::{require_permissions, Claims, Permissions},
+    error::Error,
+    models::AuditLogEntry,
+    AppState,
+};
+
+const DEFAULT_AUDIT_LOG_LIMIT: i64 = 100;
+const MAX_AUDIT_LOG_LIMIT: i64 = 500;
+
+#[derive(Serialize, Deserialize)]
+pub struct ListAuditLogRequest {
+    actor_id: Option<String>,
+    /// Only return entries older than this entry id, for paging through the log.
+    before: Option<i64>,
+    limit: Option<i64>,
+}
+
+/// Lists audit log entries, newest first.
+pub async fn list_audit_log(
+    State(state): State<Arc<AppState>>,
+    claims: Claims,
+    Query(ListAuditLogRequest {
+        actor_id,
+        before,
+        limit,
+    }): Query<ListAuditLogRequest>,
+) -> Result<Json<Vec<AuditLogEntry>>, Error> {
+    require_permissions(claims.permissions(), Permissions::Admin)?;
+
+    let limit = limit
+        .unwrap_or(DEFAULT_AUDIT_LOG_LIMIT)
+        .clamp(1, MAX_AUDIT_LOG_LIMIT);
+
+    let entries = sqlx::query_as::<_, AuditLogEntry>(
+        "SELECT * FROM audit_log
+        WHERE (?1 IS NULL OR actor_id = ?1)
+            AND (?2 IS NULL OR id < ?2)
+        ORDER BY id DESC
+        LIMIT ?3",
+    )
+    .bind(actor_id)
+    .bind(before)
+    .bind(limit)
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(Json(entries))
+}
@@ -3,7 +3,7 @@ use std::{collections::HashMap, sync::Arc};
 use crate::{
     auth::{Claims, DiscordTokenResponse},
     error::Error,
-    models::User,
+    models::{Ban, BanScope, User},
     AppState, ENV,
 };
 
@@ -34,6 +34,13 @@ pub async fn login(
 
     let id = discord_user.id.to_string();
 
+    if Ban::find_active(&state.db, &id)
+        .await?
+        .is_some_and(|ban| ban.restricts(BanScope::Global))
+    {
+        return Err(Error::Banned);
+    }
+
     sqlx::query!(
         "INSERT INTO users (id) VALUES (?) ON CONFLICT(id) DO NOTHING",
         id
@@ -69,7 +76,7 @@ async fn get_discord_oauth_token(
     form.insert("client_secret", &ENV.discord_client_secret);
     form.insert("redirect_uri", &ENV.discord_redirect_uri);
     form.insert("grant_type", "authorization_code");
-    form.insert("code", &code);
+    form.insert("code", code);
 
     let req = http
         .post("https://discord.com/api/oauth2/token")
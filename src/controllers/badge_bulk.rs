@@ -0,0 +1,225 @@
+use std::{collections::HashSet, sync::Arc};
+
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::SqliteConnection;
+
+use crate::{
+    audit,
+    auth::{require_permissions, Claims, Permissions},
+    controllers::badges::{
+        apply_badge_update, insert_badge, CreateBadgeRequest, UpdateBadgeRequest,
+    },
+    error::Error,
+    images,
+    models::Badge,
+    AppState,
+};
+
+/// The maximum amount of badges a bulk request may touch, counting every user of a grant.
+const MAX_BULK_ITEMS: usize = 1000;
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum BulkBadgeOperation {
+    Create(CreateBadgeRequest),
+    Update {
+        id: i64,
+        #[serde(flatten)]
+        changes: UpdateBadgeRequest,
+    },
+    Delete {
+        id: i64,
+    },
+    /// Grants a badge definition to every listed user. Users who already hold it are skipped.
+    Grant {
+        definition_id: i64,
+        user_ids: Vec<String>,
+        expires_at: Option<DateTime<Utc>>,
+    },
+}
+
+impl BulkBadgeOperation {
+    fn item_count(&self) -> usize {
+        match self {
+            BulkBadgeOperation::Grant { user_ids, .. } => user_ids.len(),
+            _ => 1,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct BulkBadgesRequest {
+    operations: Vec<BulkBadgeOperation>,
+}
+
+/// The outcome of a single operation. `badges` holds the created, updated or deleted badges, and
+/// `skipped` the users of a grant who already held the badge definition.
+#[derive(Serialize)]
+#[serde(tag = "status", rename_all = "lowercase")]
+pub enum BulkBadgeResult {
+    Ok {
+        badges: Vec<Badge>,
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        skipped: Vec<String>,
+    },
+    Error {
+        error: String,
+    },
+}
+
+#[derive(Serialize)]
+pub struct BulkBadgesResponse {
+    committed: bool,
+    results: Vec<BulkBadgeResult>,
+}
+
+#[derive(Serialize, Default)]
+struct BulkBadgesAuditDetails {
+    created: Vec<i64>,
+    updated: Vec<i64>,
+    deleted: Vec<i64>,
+}
+
+/// Creates, updates and deletes many badges in a single transaction.
+///
+/// Every operation is attempted and gets a result in the same order. If any of them fails, the
+/// whole transaction is rolled back and the response has status `400 Bad Request`.
+pub async fn bulk_badges(
+    State(state): State<Arc<AppState>>,
+    claims: Claims,
+    Json(BulkBadgesRequest { operations }): Json<BulkBadgesRequest>,
+) -> Result<Response, Error> {
+    require_permissions(claims.permissions(), Permissions::ManageBadges)?;
+
+    let item_count: usize = operations.iter().map(BulkBadgeOperation::item_count).sum();
+    if item_count > MAX_BULK_ITEMS {
+        return Err(Error::BadRequest(format!(
+            "at most {MAX_BULK_ITEMS} badges can be changed at once"
+        )));
+    }
+
+    let mut tx = state.db.begin().await?;
+    let mut details = BulkBadgesAuditDetails::default();
+    let mut results = Vec::with_capacity(operations.len());
+
+    for operation in operations {
+        let result = match apply_operation(&mut tx, operation, &mut details).await {
+            Ok((badges, skipped)) => BulkBadgeResult::Ok { badges, skipped },
+            Err(error) => BulkBadgeResult::Error {
+                error: error.into_parts().1,
+            },
+        };
+
+        results.push(result);
+    }
+
+    let committed = results
+        .iter()
+        .all(|result| matches!(result, BulkBadgeResult::Ok { .. }));
+
+    if !committed {
+        tx.rollback().await?;
+
+        let response = BulkBadgesResponse { committed, results };
+        return Ok((StatusCode::BAD_REQUEST, Json(response)).into_response());
+    }
+
+    audit::record(&mut tx, claims.user_id(), "badges.bulk", &details).await?;
+    tx.commit().await?;
+
+    state.cache.badges.invalidate();
+
+    for badge_id in details.deleted {
+        images::delete_badge_image(badge_id).await?;
+    }
+
+    Ok(Json(BulkBadgesResponse { committed, results }).into_response())
+}
+
+async fn apply_operation(
+    conn: &mut SqliteConnection,
+    operation: BulkBadgeOperation,
+    details: &mut BulkBadgesAuditDetails,
+) -> Result<(Vec<Badge>, Vec<String>), Error> {
+    match operation {
+        BulkBadgeOperation::Create(body) => {
+            let badge = insert_badge(conn, body).await?;
+            details.created.push(badge.id);
+
+            Ok((vec![badge], Vec::new()))
+        }
+        BulkBadgeOperation::Update { id, changes } => {
+            let badge = apply_badge_update(conn, id, changes).await?;
+            details.updated.push(badge.id);
+
+            Ok((vec![badge], Vec::new()))
+        }
+        BulkBadgeOperation::Delete { id } => {
+            let badge = sqlx::query_as::<_, Badge>("SELECT * FROM resolved_badges WHERE id = ?")
+                .bind(id)
+                .fetch_optional(&mut *conn)
+                .await?
+                .ok_or(Error::NotFound)?;
+
+            sqlx::query!("DELETE FROM badges WHERE id = ?", id)
+                .execute(&mut *conn)
+                .await?;
+            details.deleted.push(id);
+
+            Ok((vec![badge], Vec::new()))
+        }
+        BulkBadgeOperation::Grant {
+            definition_id,
+            user_ids,
+            expires_at,
+        } => {
+            let mut seen = HashSet::new();
+            let mut badges = Vec::with_capacity(user_ids.len());
+            let mut skipped = Vec::new();
+
+            for user_id in user_ids {
+                if !seen.insert(user_id.clone()) {
+                    continue;
+                }
+
+                let already_granted = sqlx::query!(
+                    "SELECT id FROM badges WHERE user_id = ? AND definition_id = ?",
+                    user_id,
+                    definition_id
+                )
+                .fetch_optional(&mut *conn)
+                .await?
+                .is_some();
+
+                if already_granted {
+                    skipped.push(user_id);
+                    continue;
+                }
+
+                let body = CreateBadgeRequest {
+                    user_id: user_id.clone(),
+                    definition_id: Some(definition_id),
+                    tooltip: None,
+                    badge: None,
+                    badge_type: None,
+                    expires_at,
+                };
+
+                let badge = insert_badge(conn, body).await.map_err(|error| {
+                    Error::BadRequest(format!("user {user_id}: {}", error.into_parts().1))
+                })?;
+                details.created.push(badge.id);
+                badges.push(badge);
+            }
+
+            Ok((badges, skipped))
+        }
+    }
+}
@@ -0,0 +1,239 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use axum_extra::extract::Query;
+use serde::{Deserialize, Serialize};
+use sqlx::SqliteConnection;
+
+use crate::{
+    auth::{require_permissions, Claims, Permissions},
+    error::Error,
+    images,
+    models::{Badge, BadgeCustomization, Ban, BanScope, CustomizationStatus},
+    AppState, ENV,
+};
+
+/// The maximum length of a tooltip chosen by a badge holder.
+pub const MAX_TOOLTIP_LENGTH: usize = 100;
+
+#[derive(Serialize, Deserialize)]
+pub struct CustomizeBadgeRequest {
+    tooltip: Option<String>,
+    badge: Option<String>,
+}
+
+/// Requests a change to a badge by its holder. Unless reviews are disabled, the change only becomes
+/// public once a moderator approves it. A new request replaces any pending request for the badge.
+pub async fn customize_badge(
+    State(state): State<Arc<AppState>>,
+    claims: Claims,
+    Path(badge_id): Path<i64>,
+    Json(body): Json<CustomizeBadgeRequest>,
+) -> Result<Json<BadgeCustomization>, Error> {
+    if body.tooltip.is_none() && body.badge.is_none() {
+        return Err(Error::BadRequest(
+            "at least one of `tooltip` and `badge` must be specified".into(),
+        ));
+    }
+
+    if body
+        .tooltip
+        .as_ref()
+        .is_some_and(|tooltip| tooltip.is_empty() || tooltip.chars().count() > MAX_TOOLTIP_LENGTH)
+    {
+        return Err(Error::BadRequest(format!(
+            "tooltip must be between 1 and {MAX_TOOLTIP_LENGTH} characters long"
+        )));
+    }
+
+    if body
+        .badge
+        .as_deref()
+        .is_some_and(|badge| !images::is_allowed_badge_url(badge))
+    {
+        return Err(Error::BadRequest(
+            "badge must be an uploaded badge image or an https URL on an allowed host".into(),
+        ));
+    }
+
+    let badge = sqlx::query_as::<_, Badge>("SELECT * FROM resolved_badges WHERE id = ?")
+        .bind(badge_id)
+        .fetch_optional(&state.db)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    if badge.user_id != claims.user_id() {
+        return Err(Error::NotFound);
+    }
+
+    if !ENV.self_service_badge_types.contains(&badge.badge_type) {
+        return Err(Error::BadRequest(format!(
+            "badges of type `{}` cannot be customized",
+            badge.badge_type.as_str()
+        )));
+    }
+
+    if Ban::find_active(&state.db, claims.user_id())
+        .await?
+        .is_some_and(|ban| ban.restricts(BanScope::Badges))
+    {
+        return Err(Error::Banned);
+    }
+
+    let status = if ENV.badge_review_required {
+        CustomizationStatus::Pending
+    } else {
+        CustomizationStatus::Approved
+    };
+
+    let mut tx = state.db.begin().await?;
+
+    sqlx::query!(
+        "DELETE FROM badge_customizations WHERE badge_id = ? AND status = 'pending'",
+        badge_id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    let customization = sqlx::query_as::<_, BadgeCustomization>(
+        "INSERT INTO badge_customizations (badge_id, user_id, tooltip, badge, status)
+        VALUES (?, ?, ?, ?, ?)
+        RETURNING *",
+    )
+    .bind(badge_id)
+    .bind(claims.user_id())
+    .bind(body.tooltip)
+    .bind(body.badge)
+    .bind(status)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    if status == CustomizationStatus::Approved {
+        apply_customization(&mut tx, &customization).await?;
+    }
+
+    tx.commit().await?;
+
+    if status == CustomizationStatus::Approved {
+        state.cache.badges.invalidate();
+    }
+
+    Ok(Json(customization))
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ListBadgeCustomizationsRequest {
+    status: Option<CustomizationStatus>,
+}
+
+pub async fn list_badge_customizations(
+    State(state): State<Arc<AppState>>,
+    claims: Claims,
+    Query(ListBadgeCustomizationsRequest { status }): Query<ListBadgeCustomizationsRequest>,
+) -> Result<Json<Vec<BadgeCustomization>>, Error> {
+    require_permissions(claims.permissions(), Permissions::ManageBadges)?;
+
+    let customizations = sqlx::query_as::<_, BadgeCustomization>(
+        "SELECT * FROM badge_customizations WHERE ?1 IS NULL OR status = ?1 ORDER BY id",
+    )
+    .bind(status)
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(Json(customizations))
+}
+
+pub async fn approve_badge_customization(
+    State(state): State<Arc<AppState>>,
+    claims: Claims,
+    Path(customization_id): Path<i64>,
+) -> Result<Json<BadgeCustomization>, Error> {
+    review_badge_customization(
+        &state,
+        &claims,
+        customization_id,
+        CustomizationStatus::Approved,
+    )
+    .await
+    .map(Json)
+}
+
+pub async fn reject_badge_customization(
+    State(state): State<Arc<AppState>>,
+    claims: Claims,
+    Path(customization_id): Path<i64>,
+) -> Result<Json<BadgeCustomization>, Error> {
+    review_badge_customization(
+        &state,
+        &claims,
+        customization_id,
+        CustomizationStatus::Rejected,
+    )
+    .await
+    .map(Json)
+}
+
+async fn review_badge_customization(
+    state: &AppState,
+    claims: &Claims,
+    customization_id: i64,
+    status: CustomizationStatus,
+) -> Result<BadgeCustomization, Error> {
+    require_permissions(claims.permissions(), Permissions::ManageBadges)?;
+
+    let mut tx = state.db.begin().await?;
+
+    let customization = sqlx::query_as::<_, BadgeCustomization>(
+        "UPDATE badge_customizations
+        SET status = ?, reviewed_at = CURRENT_TIMESTAMP, reviewed_by = ?
+        WHERE id = ? AND status = 'pending'
+        RETURNING *",
+    )
+    .bind(status)
+    .bind(claims.user_id())
+    .bind(customization_id)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or(Error::NotFound)?;
+
+    if status == CustomizationStatus::Approved {
+        apply_customization(&mut tx, &customization).await?;
+    }
+
+    tx.commit().await?;
+
+    if status == CustomizationStatus::Approved {
+        state.cache.badges.invalidate();
+    }
+
+    Ok(customization)
+}
+
+/// Applies a customization to its badge, unless the badge has been reassigned to another user since
+/// the customization was requested.
+async fn apply_customization(
+    conn: &mut SqliteConnection,
+    customization: &BadgeCustomization,
+) -> Result<(), Error> {
+    let response = sqlx::query!(
+        "UPDATE badges SET tooltip = COALESCE(?, tooltip), badge = COALESCE(?, badge)
+        WHERE id = ? AND user_id = ?",
+        customization.tooltip,
+        customization.badge,
+        customization.badge_id,
+        customization.user_id
+    )
+    .execute(conn)
+    .await?;
+
+    if response.rows_affected() == 0 {
+        return Err(Error::BadRequest(
+            "the badge no longer belongs to the user who requested the customization".into(),
+        ));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,130 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    auth::{require_permissions, Claims, Permissions},
+    error::Error,
+    images,
+    models::{BadgeDefinition, BadgeType},
+    AppState,
+};
+
+pub async fn list_badge_definitions(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<BadgeDefinition>>, Error> {
+    let definitions = sqlx::query_as::<_, BadgeDefinition>("SELECT * FROM badge_definitions")
+        .fetch_all(&state.db)
+        .await?;
+
+    Ok(Json(definitions))
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CreateBadgeDefinitionRequest {
+    name: String,
+    tooltip: String,
+    badge: String,
+    #[serde(default)]
+    badge_type: BadgeType,
+}
+
+pub async fn create_badge_definition(
+    State(state): State<Arc<AppState>>,
+    claims: Claims,
+    Json(body): Json<CreateBadgeDefinitionRequest>,
+) -> Result<Json<BadgeDefinition>, Error> {
+    require_permissions(claims.permissions(), Permissions::ManageBadges)?;
+
+    let created_definition = sqlx::query_as::<_, BadgeDefinition>(
+        "INSERT INTO badge_definitions (name, tooltip, badge, badge_type) VALUES (?, ?, ?, ?) RETURNING *",
+    )
+    .bind(body.name)
+    .bind(body.tooltip)
+    .bind(body.badge)
+    .bind(body.badge_type)
+    .fetch_one(&state.db)
+    .await?;
+
+    Ok(Json(created_definition))
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct UpdateBadgeDefinitionRequest {
+    name: Option<String>,
+    tooltip: Option<String>,
+    badge: Option<String>,
+    badge_type: Option<BadgeType>,
+}
+
+pub async fn update_badge_definition(
+    State(state): State<Arc<AppState>>,
+    claims: Claims,
+    Path(definition_id): Path<i64>,
+    Json(body): Json<UpdateBadgeDefinitionRequest>,
+) -> Result<Json<BadgeDefinition>, Error> {
+    require_permissions(claims.permissions(), Permissions::ManageBadges)?;
+
+    let updated_definition = sqlx::query_as::<_, BadgeDefinition>(
+        "UPDATE badge_definitions SET
+            name = COALESCE(?, name),
+            tooltip = COALESCE(?, tooltip),
+            badge = COALESCE(?, badge),
+            badge_type = COALESCE(?, badge_type)
+        WHERE id = ?
+        RETURNING *",
+    )
+    .bind(body.name)
+    .bind(body.tooltip)
+    .bind(body.badge)
+    .bind(body.badge_type)
+    .bind(definition_id)
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or(Error::NotFound)?;
+
+    state.cache.badges.invalidate();
+
+    Ok(Json(updated_definition))
+}
+
+/// Deletes a badge definition, along with every badge that was assigned from it.
+pub async fn delete_badge_definition(
+    State(state): State<Arc<AppState>>,
+    claims: Claims,
+    Path(definition_id): Path<i64>,
+) -> Result<(), Error> {
+    require_permissions(claims.permissions(), Permissions::ManageBadges)?;
+
+    let mut tx = state.db.begin().await?;
+
+    // Assigned badges are deleted with the definition, so their images have to be removed too
+    let badge_ids = sqlx::query_scalar!(
+        "SELECT id FROM badges WHERE definition_id = ?",
+        definition_id
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+
+    let response = sqlx::query!("DELETE FROM badge_definitions WHERE id = ?", definition_id)
+        .execute(&mut *tx)
+        .await?;
+
+    if response.rows_affected() == 0 {
+        return Err(Error::NotFound);
+    }
+
+    tx.commit().await?;
+
+    state.cache.badges.invalidate();
+
+    for badge_id in badge_ids {
+        images::delete_badge_image(badge_id).await?;
+    }
+
+    Ok(())
+}
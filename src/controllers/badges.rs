@@ -1,65 +1,362 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    hash::{DefaultHasher, Hash, Hasher},
+    sync::Arc,
+};
 
+use anyhow::anyhow;
 use axum::{
+    body::Bytes,
     extract::{Path, State},
+    http::{header, HeaderMap, StatusCode},
     response::{IntoResponse, Response},
     Json,
 };
-use axum_extra::extract::Query;
+use axum_extra::{
+    extract::Query,
+    headers::{ETag, IfNoneMatch},
+    TypedHeader,
+};
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use sqlx::SqliteConnection;
 
 use crate::{
     auth::{require_permissions, Claims, Permissions},
+    cache::{BadgeListKey, CachedBadgeList},
     error::Error,
-    models::Badge,
-    AppState,
+    formats::BadgeListFormat,
+    images, locale,
+    models::{Badge, BadgeType, Ban, BanScope},
+    utils::deserialize_some,
+    AppState, ENV,
 };
 
+#[derive(Serialize, Deserialize)]
+pub struct GetBadgesForUserRequest {
+    #[serde(rename = "type")]
+    badge_type: Option<BadgeType>,
+    locale: Option<String>,
+}
+
+/// Whether the claims belong to the holder of the badges of `user_id`, or to someone who can manage
+/// all badges.
+fn can_manage_badges_of(claims: &Claims, user_id: &str) -> bool {
+    claims.user_id() == user_id || claims.permissions().contains(Permissions::ManageBadges)
+}
+
+/// Returns the badges of a user in their chosen order, with their tooltips localized. Hidden badges
+/// are only included for the badge holder themselves and for badge managers.
+///
+/// Invalid tokens are treated as anonymous, like in [`list_badges`].
 pub async fn get_badges_for_user(
     State(state): State<Arc<AppState>>,
+    claims: Result<Claims, Error>,
+    headers: HeaderMap,
     Path(user_id): Path<String>,
-) -> Result<Json<Vec<Badge>>, Error> {
-    let badges = sqlx::query_as!(Badge, "SELECT * FROM badges WHERE user_id = ?", user_id)
-        .fetch_all(&state.db)
-        .await?;
+    Query(GetBadgesForUserRequest { badge_type, locale }): Query<GetBadgesForUserRequest>,
+) -> Result<Response, Error> {
+    let include_hidden = claims
+        .ok()
+        .is_some_and(|claims| can_manage_badges_of(&claims, &user_id));
 
-    Ok(Json(badges))
+    let mut badges = sqlx::query_as::<_, Badge>(
+        "SELECT * FROM resolved_badges
+        WHERE user_id = ?1
+            AND (?2 IS NULL OR badge_type = ?2)
+            AND (?3 OR NOT hidden)
+            AND (expires_at IS NULL OR datetime(expires_at) > datetime('now'))
+        ORDER BY position, id",
+    )
+    .bind(user_id)
+    .bind(badge_type)
+    .bind(include_hidden)
+    .fetch_all(&state.db)
+    .await?;
+
+    let locales = locale::preferred_locales(locale.as_deref(), &headers);
+    locale::localize_badges(&state.db, &mut badges, &locales).await?;
+
+    Ok(([(header::VARY, "Accept-Language")], Json(badges)).into_response())
+}
+
+/// Discord snowflakes have at least 17 digits, which badge ids never reach. This lets the
+/// deprecated `GET /v2/badges/{user_id}` alias share its path with `GET /v2/badges/{badge_id}`.
+const MIN_SNOWFLAKE: u64 = 10_000_000_000_000_000;
+
+/// The date `GET /v2/badges/{user_id}` was deprecated, as an RFC 9745 `Deprecation` value.
+const USER_BADGES_ALIAS_DEPRECATION: &str = "@1792368000";
+
+/// Returns a single badge, with its tooltip localized. Hidden badges are only returned to the badge
+/// holder themselves and to badge managers.
+///
+/// When called with a user id, this behaves like [`get_badges_for_user`] and marks the response as
+/// deprecated. Invalid tokens are treated as anonymous, like in [`list_badges`].
+pub async fn get_badge(
+    State(state): State<Arc<AppState>>,
+    claims: Result<Claims, Error>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    Query(query): Query<GetBadgesForUserRequest>,
+) -> Result<Response, Error> {
+    let id: u64 = id.parse().map_err(|_| Error::NotFound)?;
+
+    if id >= MIN_SNOWFLAKE {
+        let user_id = id.to_string();
+        let successor = format!("</v2/users/{user_id}/badges>; rel=\"successor-version\"");
+
+        let mut response =
+            get_badges_for_user(State(state), claims, headers, Path(user_id), Query(query)).await?;
+
+        let response_headers = response.headers_mut();
+        response_headers.insert(
+            "Deprecation",
+            header::HeaderValue::from_static(USER_BADGES_ALIAS_DEPRECATION),
+        );
+        response_headers.insert(
+            header::LINK,
+            header::HeaderValue::from_str(&successor)
+                .map_err(|_| Error::Other(anyhow!("invalid successor link")))?,
+        );
+
+        return Ok(response);
+    }
+
+    let mut badge = sqlx::query_as::<_, Badge>(
+        "SELECT * FROM resolved_badges
+        WHERE id = ?
+            AND (expires_at IS NULL OR datetime(expires_at) > datetime('now'))",
+    )
+    .bind(id as i64)
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or(Error::NotFound)?;
+
+    if badge.hidden
+        && !claims
+            .ok()
+            .is_some_and(|claims| can_manage_badges_of(&claims, &badge.user_id))
+    {
+        return Err(Error::NotFound);
+    }
+
+    let locales = locale::preferred_locales(query.locale.as_deref(), &headers);
+    locale::localize_badges(&state.db, std::slice::from_mut(&mut badge), &locales).await?;
+
+    Ok(([(header::VARY, "Accept-Language")], Json(badge)).into_response())
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct ListBadgesRequest {
-    format: Option<String>,
+    #[serde(default)]
+    format: BadgeListFormat,
+    #[serde(rename = "type")]
+    badge_type: Option<BadgeType>,
+    locale: Option<String>,
 }
 
+/// Lists all public badges, with their tooltips localized.
+///
+/// The serialized response is cached until the next badge write, or until a badge in it expires. It
+/// carries an `ETag` derived from the badge revision counter, the next expiry and the query, so
+/// clients can poll with `If-None-Match` and receive `304 Not Modified` until a badge changes.
+///
+/// Invalid tokens are treated as anonymous, so clients with an expired token can still read the
+/// public list.
 pub async fn list_badges(
     State(state): State<Arc<AppState>>,
-    Query(ListBadgesRequest { format }): Query<ListBadgesRequest>,
+    claims: Result<Claims, Error>,
+    if_none_match: Option<TypedHeader<IfNoneMatch>>,
+    headers: HeaderMap,
+    Query(ListBadgesRequest {
+        format,
+        badge_type,
+        locale,
+    }): Query<ListBadgesRequest>,
 ) -> Result<Response, Error> {
-    let badges = sqlx::query_as!(Badge, "SELECT * FROM badges")
-        .fetch_all(&state.db)
+    if format == BadgeListFormat::Csv {
+        let claims = claims?;
+        require_permissions(claims.permissions(), Permissions::ManageBadges)?;
+    }
+
+    let key = BadgeListKey {
+        format,
+        badge_type,
+        locales: locale::resolve_locales(
+            &state,
+            locale::preferred_locales(locale.as_deref(), &headers),
+        )
+        .await?,
+    };
+
+    let cached = match state
+        .cache
+        .badges
+        .get(&key)
+        .filter(CachedBadgeList::is_fresh)
+    {
+        Some(cached) => cached,
+        None => {
+            let generation = state.cache.badges.generation();
+            let cached = build_badge_list(&state, &key).await?;
+            state.cache.badges.insert(generation, key, cached.clone());
+            cached
+        }
+    };
+
+    // Staff exports must not be stored in shared caches
+    let cache_control = match format {
+        BadgeListFormat::Csv => "private, no-cache",
+        _ => ENV.badges_cache_control.as_str(),
+    };
+
+    let headers = (
+        TypedHeader(cached.etag.clone()),
+        [
+            (header::CONTENT_TYPE, format.content_type()),
+            (header::CACHE_CONTROL, cache_control),
+            (header::VARY, "Accept-Language"),
+        ],
+    );
+
+    if if_none_match
+        .is_some_and(|TypedHeader(if_none_match)| !if_none_match.precondition_passes(&cached.etag))
+    {
+        return Ok((StatusCode::NOT_MODIFIED, headers).into_response());
+    }
+
+    Ok((headers, cached.body).into_response())
+}
+
+async fn build_badge_list(state: &AppState, key: &BadgeListKey) -> Result<CachedBadgeList, Error> {
+    let mut tx = state.db.begin().await?;
+
+    let revision = sqlx::query_scalar!("SELECT revision FROM revisions WHERE name = 'badges'")
+        .fetch_one(&mut *tx)
+        .await?;
+
+    let mut badges = sqlx::query_as::<_, Badge>(
+        "SELECT * FROM resolved_badges
+        WHERE (?1 IS NULL OR badge_type = ?1)
+            AND NOT hidden
+            AND (expires_at IS NULL OR datetime(expires_at) > datetime('now'))
+        ORDER BY position, id",
+    )
+    .bind(key.badge_type)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    locale::localize_badges(&state.db, &mut badges, &key.locales).await?;
+
+    let body = key.format.render(&badges)?;
+
+    // Expired badges are filtered out without changing the revision, so the next expiry is part of
+    // the `ETag` as well
+    let expires_at = badges.iter().filter_map(|badge| badge.expires_at).min();
+
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    expires_at.hash(&mut hasher);
+    let etag = format!("\"{revision}-{:x}\"", hasher.finish())
+        .parse()
+        .map_err(|_| Error::Other(anyhow!("failed to create badge list etag")))?;
+
+    Ok(CachedBadgeList {
+        etag,
+        body: body.into(),
+        expires_at,
+    })
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ListBadgeChangesRequest {
+    since: i64,
+}
+
+#[derive(Serialize, Deserialize, sqlx::FromRow)]
+pub struct DeletedBadge {
+    id: i64,
+    user_id: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct BadgeChanges {
+    /// The latest revision, to be used as `since` for the next request.
+    revision: i64,
+    created: Vec<Badge>,
+    updated: Vec<Badge>,
+    /// Badges that were deleted, or are no longer public because they were hidden or expired.
+    deleted: Vec<DeletedBadge>,
+}
+
+/// Lists all changes to public badges after the revision `since`.
+///
+/// Clients that already hold the badge list can use this to stay in sync, without downloading the
+/// whole list again.
+pub async fn list_badge_changes(
+    State(state): State<Arc<AppState>>,
+    Query(ListBadgeChangesRequest { since }): Query<ListBadgeChangesRequest>,
+) -> Result<Json<BadgeChanges>, Error> {
+    let mut tx = state.db.begin().await?;
+
+    let revision = sqlx::query_scalar!("SELECT revision FROM revisions WHERE name = 'badges'")
+        .fetch_one(&mut *tx)
         .await?;
 
-    if let Some(format) = format {
-        if format == "object" {
-            let mut object = HashMap::<String, Vec<Badge>>::new();
-            for badge in badges {
-                object
-                    .entry(badge.user_id.clone())
-                    .or_insert_with(Vec::new)
-                    .push(badge);
-            }
-            return Ok(Json(object).into_response());
+    let changed_badges = sqlx::query_as::<_, Badge>(
+        "SELECT * FROM resolved_badges WHERE revision > ? ORDER BY revision",
+    )
+    .bind(since)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    let mut deleted = sqlx::query_as::<_, DeletedBadge>(
+        "SELECT id, user_id FROM badge_tombstones WHERE revision > ? ORDER BY revision",
+    )
+    .bind(since)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    let now = Utc::now();
+    let mut created = Vec::new();
+    let mut updated = Vec::new();
+
+    for badge in changed_badges {
+        if badge.hidden || badge.expires_at.is_some_and(|expires_at| expires_at <= now) {
+            deleted.push(DeletedBadge {
+                id: badge.id,
+                user_id: badge.user_id,
+            });
+        } else if badge.created_revision > since {
+            created.push(badge);
+        } else {
+            updated.push(badge);
         }
     }
 
-    Ok(Json(badges).into_response())
+    Ok(Json(BadgeChanges {
+        revision,
+        created,
+        updated,
+        deleted,
+    }))
 }
 
+/// Creates a badge for a user, either from a badge definition or standalone.
+///
+/// If `definition_id` is specified, the other fields override the values of the definition for
+/// this user only. Otherwise, `tooltip` and `badge` are required.
 #[derive(Serialize, Deserialize)]
 pub struct CreateBadgeRequest {
-    user_id: String,
-    tooltip: String,
-    badge: String,
+    pub(crate) user_id: String,
+    pub(crate) definition_id: Option<i64>,
+    pub(crate) tooltip: Option<String>,
+    pub(crate) badge: Option<String>,
+    pub(crate) badge_type: Option<BadgeType>,
+    pub(crate) expires_at: Option<DateTime<Utc>>,
 }
 
 pub async fn create_badge(
@@ -69,22 +366,294 @@ pub async fn create_badge(
 ) -> Result<(), Error> {
     require_permissions(claims.permissions(), Permissions::ManageBadges)?;
 
-    sqlx::query!(
-        "INSERT INTO badges (user_id, tooltip, badge) VALUES (?, ?, ?)",
+    let mut tx = state.db.begin().await?;
+    insert_badge(&mut tx, body).await?;
+    tx.commit().await?;
+
+    state.cache.badges.invalidate();
+
+    Ok(())
+}
+
+/// Validates and inserts a badge, returning it resolved against its definition.
+pub(crate) async fn insert_badge(
+    conn: &mut SqliteConnection,
+    body: CreateBadgeRequest,
+) -> Result<Badge, Error> {
+    if Ban::find_active(&mut *conn, &body.user_id)
+        .await?
+        .is_some_and(|ban| ban.restricts(BanScope::Badges))
+    {
+        return Err(Error::Banned);
+    }
+
+    if body
+        .expires_at
+        .is_some_and(|expires_at| expires_at <= Utc::now())
+    {
+        return Err(Error::BadRequest(
+            "badge expiry must be in the future".into(),
+        ));
+    }
+
+    let badge_type = match body.definition_id {
+        Some(_) => body.badge_type,
+        None if body.tooltip.is_none() || body.badge.is_none() => {
+            return Err(Error::BadRequest(
+                "`tooltip` and `badge` are required without `definition_id`".into(),
+            ))
+        }
+        None => Some(body.badge_type.unwrap_or_default()),
+    };
+
+    let badge_id = sqlx::query_scalar!(
+        r#"INSERT INTO badges (user_id, definition_id, tooltip, badge, badge_type, expires_at) VALUES (?, ?, ?, ?, ?, ?)
+        RETURNING id AS "id!""#,
         body.user_id,
+        body.definition_id,
         body.tooltip,
-        body.badge
+        body.badge,
+        badge_type,
+        body.expires_at
     )
-    .execute(&state.db)
+    .fetch_one(&mut *conn)
     .await?;
 
-    Ok(())
+    let badge = sqlx::query_as::<_, Badge>("SELECT * FROM resolved_badges WHERE id = ?")
+        .bind(badge_id)
+        .fetch_one(&mut *conn)
+        .await?;
+
+    Ok(badge)
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct UpdateBadgeRequest {
+    pub(crate) user_id: Option<String>,
+    definition_id: Option<i64>,
+    /// Set to `null` to remove the override and use the tooltip of the definition.
+    #[serde(default, deserialize_with = "deserialize_some")]
+    tooltip: Option<Option<String>>,
+    /// Set to `null` to remove the override and use the badge of the definition.
+    #[serde(default, deserialize_with = "deserialize_some")]
+    badge: Option<Option<String>>,
+    /// Set to `null` to remove the override and use the badge type of the definition.
+    #[serde(default, deserialize_with = "deserialize_some")]
+    badge_type: Option<Option<BadgeType>>,
+    /// Set to `null` to make the badge permanent.
+    #[serde(default, deserialize_with = "deserialize_some")]
+    expires_at: Option<Option<DateTime<Utc>>>,
+}
+
+pub async fn update_badge(
+    State(state): State<Arc<AppState>>,
+    claims: Claims,
+    Path(badge_id): Path<i64>,
+    Json(body): Json<UpdateBadgeRequest>,
+) -> Result<Json<Badge>, Error> {
+    require_permissions(claims.permissions(), Permissions::ManageBadges)?;
+
+    let mut tx = state.db.begin().await?;
+    let updated_badge = apply_badge_update(&mut tx, badge_id, body).await?;
+    tx.commit().await?;
+
+    state.cache.badges.invalidate();
+
+    Ok(Json(updated_badge))
+}
+
+/// Validates and applies changes to a badge, returning it resolved against its definition.
+pub(crate) async fn apply_badge_update(
+    conn: &mut SqliteConnection,
+    badge_id: i64,
+    body: UpdateBadgeRequest,
+) -> Result<Badge, Error> {
+    if body
+        .expires_at
+        .flatten()
+        .is_some_and(|expires_at| expires_at <= Utc::now())
+    {
+        return Err(Error::BadRequest(
+            "badge expiry must be in the future".into(),
+        ));
+    }
+
+    if let Some(user_id) = &body.user_id {
+        if Ban::find_active(&mut *conn, user_id)
+            .await?
+            .is_some_and(|ban| ban.restricts(BanScope::Badges))
+        {
+            return Err(Error::Banned);
+        }
+    }
+
+    let update_tooltip = body.tooltip.is_some();
+    let tooltip = body.tooltip.flatten();
+    let update_badge = body.badge.is_some();
+    let badge = body.badge.flatten();
+    let update_badge_type = body.badge_type.is_some();
+    let badge_type = body.badge_type.flatten();
+    let update_expires_at = body.expires_at.is_some();
+    let expires_at = body.expires_at.flatten();
+
+    let response = sqlx::query!(
+        "UPDATE badges SET
+            user_id = COALESCE(?, user_id),
+            definition_id = COALESCE(?, definition_id),
+            tooltip = CASE WHEN ? THEN ? ELSE tooltip END,
+            badge = CASE WHEN ? THEN ? ELSE badge END,
+            badge_type = CASE WHEN ? THEN ? ELSE badge_type END,
+            expires_at = CASE WHEN ? THEN ? ELSE expires_at END
+        WHERE id = ?",
+        body.user_id,
+        body.definition_id,
+        update_tooltip,
+        tooltip,
+        update_badge,
+        badge,
+        update_badge_type,
+        badge_type,
+        update_expires_at,
+        expires_at,
+        badge_id
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(|error| match error.as_database_error() {
+        Some(db_error) if db_error.kind() == sqlx::error::ErrorKind::CheckViolation => {
+            Error::BadRequest(
+                "`tooltip`, `badge` and `badge_type` can only be removed from badges with a definition"
+                    .into(),
+            )
+        }
+        _ => Error::Db(error),
+    })?;
+
+    if response.rows_affected() == 0 {
+        return Err(Error::NotFound);
+    }
+
+    let updated_badge = sqlx::query_as::<_, Badge>("SELECT * FROM resolved_badges WHERE id = ?")
+        .bind(badge_id)
+        .fetch_one(&mut *conn)
+        .await?;
+
+    Ok(updated_badge)
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ReorderBadgesRequest {
+    badge_ids: Vec<i64>,
+}
+
+/// Moves the given badges of a user to the front, in the given order. Badges that are not listed
+/// keep their relative order after them.
+pub async fn reorder_badges(
+    State(state): State<Arc<AppState>>,
+    claims: Claims,
+    Path(user_id): Path<String>,
+    Json(ReorderBadgesRequest { badge_ids }): Json<ReorderBadgesRequest>,
+) -> Result<Json<Vec<Badge>>, Error> {
+    if !can_manage_badges_of(&claims, &user_id) {
+        return Err(Error::MissingPermissions {
+            missing_permissions: Permissions::ManageBadges,
+        });
+    }
+
+    let mut tx = state.db.begin().await?;
+
+    let existing_ids = sqlx::query_scalar!(
+        "SELECT id AS \"id!\" FROM badges WHERE user_id = ? ORDER BY position, id",
+        user_id
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+
+    if let Some(badge_id) = badge_ids.iter().find(|id| !existing_ids.contains(id)) {
+        return Err(Error::BadRequest(format!(
+            "badge {badge_id} does not belong to this user"
+        )));
+    }
+
+    let ordered_ids = badge_ids.iter().chain(
+        existing_ids
+            .iter()
+            .filter(|badge_id| !badge_ids.contains(badge_id)),
+    );
+
+    for (position, badge_id) in (0_i64..).zip(ordered_ids) {
+        sqlx::query!(
+            "UPDATE badges SET position = ? WHERE id = ?",
+            position,
+            badge_id
+        )
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    let badges = sqlx::query_as::<_, Badge>(
+        "SELECT * FROM resolved_badges WHERE user_id = ? ORDER BY position, id",
+    )
+    .bind(&user_id)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    state.cache.badges.invalidate();
+
+    Ok(Json(badges))
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SetBadgeVisibilityRequest {
+    hidden: bool,
+}
+
+/// Hides or shows a badge in public listings. Can be used by the badge holder themselves.
+pub async fn set_badge_visibility(
+    State(state): State<Arc<AppState>>,
+    claims: Claims,
+    Path(badge_id): Path<i64>,
+    Json(SetBadgeVisibilityRequest { hidden }): Json<SetBadgeVisibilityRequest>,
+) -> Result<Json<Badge>, Error> {
+    let mut tx = state.db.begin().await?;
+
+    let user_id = sqlx::query_scalar!("SELECT user_id FROM badges WHERE id = ?", badge_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    if !can_manage_badges_of(&claims, &user_id) {
+        return Err(Error::MissingPermissions {
+            missing_permissions: Permissions::ManageBadges,
+        });
+    }
+
+    sqlx::query!(
+        "UPDATE badges SET hidden = ? WHERE id = ?",
+        hidden,
+        badge_id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    let updated_badge = sqlx::query_as::<_, Badge>("SELECT * FROM resolved_badges WHERE id = ?")
+        .bind(badge_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+
+    state.cache.badges.invalidate();
+
+    Ok(Json(updated_badge))
 }
 
 pub async fn delete_badge(
     State(state): State<Arc<AppState>>,
     claims: Claims,
-    Path(badge_id): Path<String>,
+    Path(badge_id): Path<i64>,
 ) -> Result<(), Error> {
     require_permissions(claims.permissions(), Permissions::ManageBadges)?;
 
@@ -93,8 +662,97 @@ pub async fn delete_badge(
         .await?;
 
     if response.rows_affected() != 0 {
+        state.cache.badges.invalidate();
+        images::delete_badge_image(badge_id).await?;
         Ok(())
     } else {
         Err(Error::NotFound)
     }
 }
+
+/// Stores an image for a badge and points the badge at it. Only available when `PUBLIC_URL` is set,
+/// since badges are displayed on other origins and need an absolute image URL.
+pub async fn upload_badge_image(
+    State(state): State<Arc<AppState>>,
+    claims: Claims,
+    Path(badge_id): Path<i64>,
+    body: Bytes,
+) -> Result<Json<Badge>, Error> {
+    require_permissions(claims.permissions(), Permissions::ManageBadges)?;
+
+    if ENV.public_url.is_empty() {
+        return Err(Error::NotFound);
+    }
+
+    sqlx::query!("SELECT id FROM badges WHERE id = ?", badge_id)
+        .fetch_optional(&state.db)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    let version = images::store_badge_image(badge_id, body.to_vec()).await?;
+
+    let image_url = images::badge_image_url(badge_id, &version);
+    sqlx::query!(
+        "UPDATE badges SET badge = ? WHERE id = ?",
+        image_url,
+        badge_id
+    )
+    .execute(&state.db)
+    .await?;
+
+    state.cache.badges.invalidate();
+
+    let updated_badge = sqlx::query_as::<_, Badge>("SELECT * FROM resolved_badges WHERE id = ?")
+        .bind(badge_id)
+        .fetch_one(&state.db)
+        .await?;
+
+    Ok(Json(updated_badge))
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct GetBadgeImageRequest {
+    size: Option<u32>,
+    /// The version of the image from its URL, which changes whenever the image is replaced.
+    v: Option<String>,
+}
+
+/// Serves a badge image. URLs with a version are cached for a day, other URLs must be revalidated
+/// with their `ETag`.
+pub async fn get_badge_image(
+    Path(badge_id): Path<i64>,
+    if_none_match: Option<TypedHeader<IfNoneMatch>>,
+    Query(GetBadgeImageRequest { size, v }): Query<GetBadgeImageRequest>,
+) -> Result<Response, Error> {
+    let (content_type, data) = images::load_badge_image(badge_id, size).await?;
+
+    let etag: ETag = format!("\"{}\"", images::image_hash(&data))
+        .parse()
+        .map_err(|_| Error::Other(anyhow!("invalid badge image etag")))?;
+
+    let cache_control = match v {
+        Some(_) => "public, max-age=86400",
+        None => "public, no-cache",
+    };
+
+    if if_none_match
+        .is_some_and(|TypedHeader(if_none_match)| !if_none_match.precondition_passes(&etag))
+    {
+        return Ok((
+            StatusCode::NOT_MODIFIED,
+            TypedHeader(etag),
+            [(header::CACHE_CONTROL, cache_control)],
+        )
+            .into_response());
+    }
+
+    Ok((
+        TypedHeader(etag),
+        [
+            (header::CONTENT_TYPE, content_type),
+            (header::CACHE_CONTROL, cache_control),
+        ],
+        data,
+    )
+        .into_response())
+}
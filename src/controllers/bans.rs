@@ -1,29 +1,50 @@
-use std::sync::Arc;
+use std::{str::FromStr, sync::Arc};
 
+use anyhow::anyhow;
 use axum::{
+    body::Bytes,
     extract::{Path, State},
+    http::header,
+    response::{IntoResponse, Response},
     Json,
 };
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 
 use crate::{
     auth::{require_permissions, Claims, Permissions},
     error::Error,
-    models::Ban,
+    models::{Ban, BanScope},
     AppState,
 };
 
 pub async fn get_ban(
     State(state): State<Arc<AppState>>,
     Path(user_id): Path<String>,
-) -> Result<Json<Ban>, Error> {
-    let ban = sqlx::query_as::<_, Ban>("SELECT * FROM bans WHERE user_id = ?")
-        .bind(user_id)
-        .fetch_one(&state.db)
-        .await?;
+) -> Result<Response, Error> {
+    let cached = match state.cache.bans.get(&user_id) {
+        Some(cached) => cached,
+        None => {
+            let generation = state.cache.bans.generation();
+
+            let ban = sqlx::query_as::<_, Ban>("SELECT * FROM bans WHERE user_id = ?")
+                .bind(&user_id)
+                .fetch_optional(&state.db)
+                .await?;
+
+            let cached = ban
+                .map(|ban| serde_json::to_vec(&ban).map(Bytes::from))
+                .transpose()
+                .map_err(|error| Error::Other(anyhow!(error)))?;
+
+            state.cache.bans.insert(generation, user_id, cached.clone());
+            cached
+        }
+    };
 
-    Ok(Json(ban))
+    let body = cached.ok_or(Error::Db(sqlx::Error::RowNotFound))?;
+
+    Ok(([(header::CONTENT_TYPE, "application/json")], body).into_response())
 }
 
 pub async fn list_bans(
@@ -39,11 +60,70 @@ pub async fn list_bans(
     Ok(Json(bans))
 }
 
+/// A ban length relative to the time the ban is created, for example `7d`, `12h` or `permanent`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum BanDuration {
+    Permanent,
+    Limited(Duration),
+}
+
+impl FromStr for BanDuration {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "permanent" {
+            return Ok(Self::Permanent);
+        }
+
+        let unit_start = s
+            .find(|c: char| !c.is_ascii_digit())
+            .ok_or_else(|| format!("missing unit in duration `{s}`"))?;
+        let (amount, unit) = s.split_at(unit_start);
+
+        let amount: i64 = amount
+            .parse()
+            .map_err(|_| format!("invalid amount in duration `{s}`"))?;
+
+        let duration = match unit {
+            "s" => Duration::try_seconds(amount),
+            "m" => Duration::try_minutes(amount),
+            "h" => Duration::try_hours(amount),
+            "d" => Duration::try_days(amount),
+            "w" => Duration::try_weeks(amount),
+            _ => return Err(format!("unknown unit `{unit}` in duration `{s}`")),
+        }
+        .ok_or_else(|| format!("duration `{s}` is too long"))?;
+
+        Ok(Self::Limited(duration))
+    }
+}
+
+impl TryFrom<String> for BanDuration {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<BanDuration> for String {
+    fn from(value: BanDuration) -> Self {
+        match value {
+            BanDuration::Permanent => "permanent".to_owned(),
+            BanDuration::Limited(duration) => format!("{}s", duration.num_seconds()),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct CreateBanRequest {
     user_id: String,
     reason: Option<String>,
     expires: Option<DateTime<Utc>>,
+    duration: Option<BanDuration>,
+    #[serde(default)]
+    scope: BanScope,
 }
 
 pub async fn create_ban(
@@ -53,14 +133,44 @@ pub async fn create_ban(
 ) -> Result<Json<Ban>, Error> {
     require_permissions(claims.permissions(), Permissions::ManageBans)?;
 
+    let now = Utc::now();
+
+    let expires = match (body.expires, body.duration) {
+        (Some(_), Some(_)) => {
+            return Err(Error::BadRequest(
+                "only one of `expires` and `duration` may be specified".into(),
+            ))
+        }
+        (expires, None) => expires,
+        (None, Some(BanDuration::Permanent)) => None,
+        (None, Some(BanDuration::Limited(duration))) => Some(
+            now.checked_add_signed(duration)
+                .ok_or_else(|| Error::BadRequest("ban duration is too long".into()))?,
+        ),
+    };
+
+    if expires.is_some_and(|expires| expires <= now) {
+        return Err(Error::BadRequest("ban expiry must be in the future".into()));
+    }
+
+    if body.scope.is_none() || !BanScope::all_flags().contains(body.scope) {
+        return Err(Error::BadRequest(format!(
+            "ban scope must be a combination of the bits in {}",
+            BanScope::all_flags().bits()
+        )));
+    }
+
+    let scope = body.scope.bits();
+
     let created_ban = {
         let mut tx = state.db.begin().await?;
 
         sqlx::query!(
-            "INSERT INTO bans (user_id, reason, expires) VALUES (?, ?, ?)",
+            "INSERT INTO bans (user_id, reason, expires, scope) VALUES (?, ?, ?, ?)",
             body.user_id,
             body.reason,
-            body.expires
+            expires,
+            scope
         )
         .execute(&mut *tx)
         .await?;
@@ -75,6 +185,8 @@ pub async fn create_ban(
         created_ban
     };
 
+    state.cache.bans.invalidate_key(&created_ban.user_id);
+
     Ok(Json(created_ban))
 }
 
@@ -90,8 +202,72 @@ pub async fn delete_ban(
         .await?;
 
     if response.rows_affected() != 0 {
+        state.cache.bans.invalidate_key(&user_id);
         Ok(())
     } else {
         Err(Error::NotFound)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_durations() {
+        let cases = [
+            ("permanent", BanDuration::Permanent),
+            ("30s", BanDuration::Limited(Duration::seconds(30))),
+            ("15m", BanDuration::Limited(Duration::minutes(15))),
+            ("12h", BanDuration::Limited(Duration::hours(12))),
+            ("7d", BanDuration::Limited(Duration::days(7))),
+            ("2w", BanDuration::Limited(Duration::weeks(2))),
+            ("0d", BanDuration::Limited(Duration::zero())),
+            ("007d", BanDuration::Limited(Duration::days(7))),
+        ];
+
+        for (input, expected) in cases {
+            assert_eq!(input.parse::<BanDuration>(), Ok(expected), "{input}");
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_durations() {
+        let cases = [
+            ("", "missing unit in duration ``"),
+            ("12", "missing unit in duration `12`"),
+            ("d", "invalid amount in duration `d`"),
+            ("-1d", "invalid amount in duration `-1d`"),
+            (" 7d", "invalid amount in duration ` 7d`"),
+            (
+                "99999999999999999999d",
+                "invalid amount in duration `99999999999999999999d`",
+            ),
+            ("7", "missing unit in duration `7`"),
+            ("7D", "unknown unit `D` in duration `7D`"),
+            ("7y", "unknown unit `y` in duration `7y`"),
+            ("7days", "unknown unit `days` in duration `7days`"),
+            ("1.5h", "unknown unit `.5h` in duration `1.5h`"),
+            ("7d ", "unknown unit `d ` in duration `7d `"),
+            ("Permanent", "invalid amount in duration `Permanent`"),
+            ("9999999999999w", "duration `9999999999999w` is too long"),
+        ];
+
+        for (input, expected) in cases {
+            assert_eq!(
+                input.parse::<BanDuration>(),
+                Err(expected.to_owned()),
+                "{input}"
+            );
+        }
+    }
+
+    #[test]
+    fn serializes_durations_in_seconds() {
+        assert_eq!(String::from(BanDuration::Permanent), "permanent");
+        assert_eq!(
+            String::from(BanDuration::Limited(Duration::hours(1))),
+            "3600s"
+        );
+    }
+}
@@ -0,0 +1,190 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    auth::{require_permissions, Claims, Permissions},
+    error::Error,
+    models::{Badge, Ban, BanScope, ClaimCode},
+    AppState,
+};
+
+pub async fn list_claim_codes(
+    State(state): State<Arc<AppState>>,
+    claims: Claims,
+) -> Result<Json<Vec<ClaimCode>>, Error> {
+    require_permissions(claims.permissions(), Permissions::ManageBadges)?;
+
+    let codes =
+        sqlx::query_as::<_, ClaimCode>("SELECT * FROM badge_claim_codes ORDER BY created_at")
+            .fetch_all(&state.db)
+            .await?;
+
+    Ok(Json(codes))
+}
+
+/// Creates a claim code for a badge definition. A random code is generated if `code` is not
+/// specified, and the code can be redeemed an unlimited amount of times if `max_uses` is not.
+#[derive(Serialize, Deserialize)]
+pub struct CreateClaimCodeRequest {
+    definition_id: i64,
+    code: Option<String>,
+    max_uses: Option<i64>,
+    expires_at: Option<DateTime<Utc>>,
+}
+
+pub async fn create_claim_code(
+    State(state): State<Arc<AppState>>,
+    claims: Claims,
+    Json(body): Json<CreateClaimCodeRequest>,
+) -> Result<Json<ClaimCode>, Error> {
+    require_permissions(claims.permissions(), Permissions::ManageBadges)?;
+
+    if body.code.as_ref().is_some_and(|code| code.is_empty()) {
+        return Err(Error::BadRequest("code must not be empty".into()));
+    }
+
+    if body.max_uses.is_some_and(|max_uses| max_uses < 1) {
+        return Err(Error::BadRequest("max_uses must be at least 1".into()));
+    }
+
+    if body
+        .expires_at
+        .is_some_and(|expires_at| expires_at <= Utc::now())
+    {
+        return Err(Error::BadRequest(
+            "claim code expiry must be in the future".into(),
+        ));
+    }
+
+    let code = sqlx::query_as::<_, ClaimCode>(
+        "INSERT INTO badge_claim_codes (code, definition_id, max_uses, expires_at, created_by)
+        VALUES (COALESCE(?, lower(hex(randomblob(8)))), ?, ?, ?, ?)
+        RETURNING *",
+    )
+    .bind(body.code)
+    .bind(body.definition_id)
+    .bind(body.max_uses)
+    .bind(body.expires_at)
+    .bind(claims.user_id())
+    .fetch_one(&state.db)
+    .await?;
+
+    Ok(Json(code))
+}
+
+pub async fn delete_claim_code(
+    State(state): State<Arc<AppState>>,
+    claims: Claims,
+    Path(code): Path<String>,
+) -> Result<(), Error> {
+    require_permissions(claims.permissions(), Permissions::ManageBadges)?;
+
+    let response = sqlx::query!("DELETE FROM badge_claim_codes WHERE code = ?", code)
+        .execute(&state.db)
+        .await?;
+
+    if response.rows_affected() != 0 {
+        Ok(())
+    } else {
+        Err(Error::NotFound)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RedeemClaimCodeRequest {
+    code: String,
+}
+
+/// Redeems a claim code, granting its badge to the logged in user. Each user can redeem a code
+/// only once.
+pub async fn redeem_claim_code(
+    State(state): State<Arc<AppState>>,
+    claims: Claims,
+    Json(RedeemClaimCodeRequest { code }): Json<RedeemClaimCodeRequest>,
+) -> Result<Json<Badge>, Error> {
+    let user_id = claims.user_id();
+
+    if Ban::find_active(&state.db, user_id)
+        .await?
+        .is_some_and(|ban| ban.restricts(BanScope::Badges))
+    {
+        return Err(Error::Banned);
+    }
+
+    let mut tx = state.db.begin().await?;
+
+    let claim_code =
+        sqlx::query_as::<_, ClaimCode>("SELECT * FROM badge_claim_codes WHERE code = ?")
+            .bind(&code)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or(Error::NotFound)?;
+
+    if claim_code
+        .expires_at
+        .is_some_and(|expires_at| expires_at <= Utc::now())
+    {
+        return Err(Error::BadRequest("claim code has expired".into()));
+    }
+
+    let already_redeemed = sqlx::query!(
+        "SELECT user_id FROM badge_claim_redemptions WHERE code = ? AND user_id = ?",
+        code,
+        user_id
+    )
+    .fetch_optional(&mut *tx)
+    .await?
+    .is_some();
+
+    if already_redeemed {
+        return Err(Error::BadRequest(
+            "claim code has already been redeemed".into(),
+        ));
+    }
+
+    let response = sqlx::query!(
+        "UPDATE badge_claim_codes SET uses = uses + 1
+        WHERE code = ? AND (max_uses IS NULL OR uses < max_uses)",
+        code
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    if response.rows_affected() == 0 {
+        return Err(Error::BadRequest("claim code has been used up".into()));
+    }
+
+    let badge_id = sqlx::query_scalar!(
+        "INSERT INTO badges (user_id, definition_id) VALUES (?, ?) RETURNING id",
+        user_id,
+        claim_code.definition_id
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        "INSERT INTO badge_claim_redemptions (code, user_id, badge_id) VALUES (?, ?, ?)",
+        code,
+        user_id,
+        badge_id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    let badge = sqlx::query_as::<_, Badge>("SELECT * FROM resolved_badges WHERE id = ?")
+        .bind(badge_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+
+    state.cache.badges.invalidate();
+
+    Ok(Json(badge))
+}
@@ -0,0 +1,94 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use axum_extra::extract::Query;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    auth::{require_permissions, Claims, Permissions},
+    error::Error,
+    jobs::contributor_sync::{self, ContributorSyncReport},
+    models::ContributorLink,
+    AppState,
+};
+
+pub async fn list_contributor_links(
+    State(state): State<Arc<AppState>>,
+    claims: Claims,
+) -> Result<Json<Vec<ContributorLink>>, Error> {
+    require_permissions(claims.permissions(), Permissions::ManageBadges)?;
+
+    let links = sqlx::query_as::<_, ContributorLink>("SELECT * FROM contributor_links")
+        .fetch_all(&state.db)
+        .await?;
+
+    Ok(Json(links))
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SetContributorLinkRequest {
+    user_id: String,
+}
+
+/// Links a forge account to a Discord account, replacing any previous link of the forge account.
+/// The badge is granted on the next sync.
+pub async fn set_contributor_link(
+    State(state): State<Arc<AppState>>,
+    claims: Claims,
+    Path(login): Path<String>,
+    Json(SetContributorLinkRequest { user_id }): Json<SetContributorLinkRequest>,
+) -> Result<Json<ContributorLink>, Error> {
+    require_permissions(claims.permissions(), Permissions::ManageBadges)?;
+
+    let link = sqlx::query_as::<_, ContributorLink>(
+        "INSERT INTO contributor_links (login, user_id) VALUES (?, ?)
+        ON CONFLICT (login) DO UPDATE SET user_id = excluded.user_id
+        RETURNING *",
+    )
+    .bind(login)
+    .bind(user_id)
+    .fetch_one(&state.db)
+    .await?;
+
+    Ok(Json(link))
+}
+
+pub async fn delete_contributor_link(
+    State(state): State<Arc<AppState>>,
+    claims: Claims,
+    Path(login): Path<String>,
+) -> Result<(), Error> {
+    require_permissions(claims.permissions(), Permissions::ManageBadges)?;
+
+    let response = sqlx::query!("DELETE FROM contributor_links WHERE login = ?", login)
+        .execute(&state.db)
+        .await?;
+
+    if response.rows_affected() != 0 {
+        Ok(())
+    } else {
+        Err(Error::NotFound)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SyncContributorsRequest {
+    #[serde(default)]
+    dry_run: bool,
+}
+
+/// Runs the contributor badge sync immediately. With `dry_run`, only reports what would change.
+pub async fn sync_contributors(
+    State(state): State<Arc<AppState>>,
+    claims: Claims,
+    Query(SyncContributorsRequest { dry_run }): Query<SyncContributorsRequest>,
+) -> Result<Json<ContributorSyncReport>, Error> {
+    require_permissions(claims.permissions(), Permissions::ManageBadges)?;
+
+    let report = contributor_sync::sync(&state, claims.user_id(), dry_run).await?;
+
+    Ok(Json(report))
+}
@@ -0,0 +1,319 @@
+use std::sync::Arc;
+
+use axum::{body::Bytes, extract::State, http::HeaderMap, Json};
+use chrono::{DateTime, Duration, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use sqlx::SqliteConnection;
+
+use crate::{
+    audit,
+    controllers::badges::{insert_badge, CreateBadgeRequest},
+    error::Error,
+    images,
+    models::BadgeType,
+    AppState, ENV,
+};
+
+/// The header carrying the hex encoded HMAC-SHA256 of the request body, prefixed with `sha256=`.
+const SIGNATURE_HEADER: &str = "X-Signature-256";
+
+/// The actor recorded in the audit log for changes made by the webhook.
+const AUDIT_ACTOR: &str = "integration:donations";
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum DonationEventType {
+    #[serde(rename = "donation.completed")]
+    Completed,
+    #[serde(rename = "donation.refunded")]
+    Refunded,
+    #[serde(rename = "donation.cancelled")]
+    Cancelled,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct DonationEvent {
+    #[serde(rename = "type")]
+    event_type: DonationEventType,
+    donation_id: String,
+    /// The Discord account linked to the donation on the donation platform.
+    discord_id: Option<String>,
+    /// When the donor badge should expire. Defaults to `DONOR_BADGE_DAYS` from now.
+    expires_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DonationOutcome {
+    /// A new donor badge was created.
+    Granted,
+    /// The existing donor badge of the user was extended.
+    Extended,
+    /// The donation was revoked, removing the donor badge unless another donation still grants it.
+    Revoked,
+    /// The event did not change anything, for example because it was already processed.
+    Ignored,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct DonationResponse {
+    status: DonationOutcome,
+}
+
+#[derive(Serialize)]
+struct DonationAuditDetails<'a> {
+    donation_id: &'a str,
+    user_id: &'a str,
+    badge_id: Option<i64>,
+}
+
+/// Verifies a `sha256=<hex>` signature of `body` in constant time.
+fn verify_signature(secret: &str, body: &[u8], signature: &str) -> bool {
+    let Some(signature) = signature
+        .strip_prefix("sha256=")
+        .and_then(|signature| hex::decode(signature).ok())
+    else {
+        return false;
+    };
+
+    let Ok(mut mac) = Hmac::<Sha256>::new_from_slice(secret.as_bytes()) else {
+        return false;
+    };
+    mac.update(body);
+
+    mac.verify_slice(&signature).is_ok()
+}
+
+/// Receives donation events and grants or revokes donor badges for the linked Discord account.
+///
+/// Every donation of a user shares a single donor badge, whose expiry is extended by each
+/// completed donation. The badge is removed once every donation granting it has been refunded or
+/// cancelled. Events are idempotent, so deliveries can safely be retried.
+///
+/// The webhook is disabled unless both `DONATIONS_WEBHOOK_SECRET` and `DONOR_BADGE_DEFINITION_ID`
+/// are set.
+pub async fn receive_donation_event(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Json<DonationResponse>, Error> {
+    let (Some(secret), Some(definition_id)) = (
+        ENV.donations_webhook_secret.as_deref(),
+        ENV.donor_badge_definition_id,
+    ) else {
+        return Err(Error::NotFound);
+    };
+
+    let signature = headers
+        .get(SIGNATURE_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .ok_or(Error::Auth)?;
+
+    if !verify_signature(secret, &body, signature) {
+        return Err(Error::Auth);
+    }
+
+    let event: DonationEvent = serde_json::from_slice(&body)
+        .map_err(|error| Error::BadRequest(format!("invalid donation event: {error}")))?;
+
+    let mut tx = state.db.begin().await?;
+
+    let (status, deleted_badge_id) = match event.event_type {
+        DonationEventType::Completed => (
+            complete_donation(&mut tx, &event, definition_id).await?,
+            None,
+        ),
+        DonationEventType::Refunded | DonationEventType::Cancelled => {
+            revoke_donation(&mut tx, &event.donation_id).await?
+        }
+    };
+
+    tx.commit().await?;
+
+    if status != DonationOutcome::Ignored {
+        state.cache.badges.invalidate();
+    }
+
+    if let Some(badge_id) = deleted_badge_id {
+        images::delete_badge_image(badge_id).await?;
+    }
+
+    Ok(Json(DonationResponse { status }))
+}
+
+async fn complete_donation(
+    conn: &mut SqliteConnection,
+    event: &DonationEvent,
+    definition_id: i64,
+) -> Result<DonationOutcome, Error> {
+    let Some(user_id) = &event.discord_id else {
+        return Ok(DonationOutcome::Ignored);
+    };
+
+    let already_processed =
+        sqlx::query!("SELECT id FROM donations WHERE id = ?", event.donation_id)
+            .fetch_optional(&mut *conn)
+            .await?
+            .is_some();
+
+    if already_processed {
+        return Ok(DonationOutcome::Ignored);
+    }
+
+    let expires_at = event
+        .expires_at
+        .unwrap_or_else(|| Utc::now() + Duration::days(ENV.donor_badge_days));
+
+    let existing_badge_id = sqlx::query_scalar!(
+        r#"SELECT badge_id AS "badge_id!" FROM donations
+        WHERE user_id = ? AND revoked_at IS NULL AND badge_id IS NOT NULL
+        LIMIT 1"#,
+        user_id
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    let (badge_id, outcome) = match existing_badge_id {
+        // The badge would already be expired. Rejecting the event would make the platform retry it
+        // forever, so the donation is recorded without a badge instead
+        _ if expires_at <= Utc::now() => (None, DonationOutcome::Ignored),
+        Some(badge_id) => {
+            sqlx::query!(
+                "UPDATE badges SET expires_at = ?1
+                WHERE id = ?2 AND (expires_at IS NOT NULL AND datetime(expires_at) < datetime(?1))",
+                expires_at,
+                badge_id
+            )
+            .execute(&mut *conn)
+            .await?;
+
+            (Some(badge_id), DonationOutcome::Extended)
+        }
+        None => {
+            let body = CreateBadgeRequest {
+                user_id: user_id.clone(),
+                definition_id: Some(definition_id),
+                tooltip: None,
+                badge: None,
+                badge_type: Some(BadgeType::Donor),
+                expires_at: Some(expires_at),
+            };
+
+            match insert_badge(conn, body).await {
+                Ok(badge) => (Some(badge.id), DonationOutcome::Granted),
+                // Still record the donation, so retried deliveries do not grant the badge either
+                Err(Error::Banned) => (None, DonationOutcome::Ignored),
+                Err(error) => return Err(error),
+            }
+        }
+    };
+
+    sqlx::query!(
+        "INSERT INTO donations (id, user_id, badge_id, expires_at) VALUES (?, ?, ?, ?)",
+        event.donation_id,
+        user_id,
+        badge_id,
+        expires_at
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    let details = DonationAuditDetails {
+        donation_id: &event.donation_id,
+        user_id,
+        badge_id,
+    };
+    audit::record(conn, AUDIT_ACTOR, "donations.complete", &details).await?;
+
+    Ok(outcome)
+}
+
+/// Revokes a donation, returning the id of the donor badge if it was deleted as a result.
+async fn revoke_donation(
+    conn: &mut SqliteConnection,
+    donation_id: &str,
+) -> Result<(DonationOutcome, Option<i64>), Error> {
+    let Some(donation) = sqlx::query!(
+        "UPDATE donations SET revoked_at = CURRENT_TIMESTAMP
+        WHERE id = ? AND revoked_at IS NULL
+        RETURNING user_id, badge_id",
+        donation_id
+    )
+    .fetch_optional(&mut *conn)
+    .await?
+    else {
+        return Ok((DonationOutcome::Ignored, None));
+    };
+
+    let mut deleted_badge_id = None;
+
+    if let Some(badge_id) = donation.badge_id {
+        let remaining = sqlx::query_scalar!(
+            "SELECT COUNT(*) FROM donations WHERE badge_id = ? AND revoked_at IS NULL",
+            badge_id
+        )
+        .fetch_one(&mut *conn)
+        .await?;
+
+        if remaining == 0 {
+            sqlx::query!("DELETE FROM badges WHERE id = ?", badge_id)
+                .execute(&mut *conn)
+                .await?;
+            deleted_badge_id = Some(badge_id);
+        }
+    }
+
+    let details = DonationAuditDetails {
+        donation_id,
+        user_id: &donation.user_id,
+        badge_id: donation.badge_id,
+    };
+    audit::record(conn, AUDIT_ACTOR, "donations.revoke", &details).await?;
+
+    Ok((DonationOutcome::Revoked, deleted_badge_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::verify_signature;
+
+    // RFC 4231, test case 2
+    const SECRET: &str = "Jefe";
+    const BODY: &[u8] = b"what do ya want for nothing?";
+    const SIGNATURE: &str =
+        "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843";
+
+    #[test]
+    fn accepts_valid_signature() {
+        assert!(verify_signature(SECRET, BODY, SIGNATURE));
+    }
+
+    #[test]
+    fn rejects_invalid_signatures() {
+        let cases = [
+            // Wrong secret
+            ("jefe", BODY, SIGNATURE),
+            // Tampered body
+            (
+                SECRET,
+                b"what do ya want for nothing!".as_slice(),
+                SIGNATURE,
+            ),
+            // Missing prefix
+            (SECRET, BODY, &SIGNATURE["sha256=".len()..]),
+            // Truncated
+            (SECRET, BODY, &SIGNATURE[..SIGNATURE.len() - 2]),
+            // Not hex
+            (SECRET, BODY, "sha256=not-hex"),
+            (SECRET, BODY, ""),
+        ];
+
+        for (secret, body, signature) in cases {
+            assert!(
+                !verify_signature(secret, body, signature),
+                "accepted {signature:?} with secret {secret:?}"
+            );
+        }
+    }
+}
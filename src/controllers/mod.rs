@@ -1,3 +1,15 @@
+pub mod audit_log;
 pub mod auth;
+pub mod badge_bulk;
+pub mod badge_customizations;
+pub mod badge_definitions;
 pub mod badges;
 pub mod bans;
+pub mod claim_codes;
+pub mod contributors;
+pub mod donations;
+pub mod metrics;
+pub mod theme_versions;
+pub mod themes;
+pub mod tooltip_translations;
+pub mod users;
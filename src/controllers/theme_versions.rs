@@ -0,0 +1,75 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    response::{IntoResponse, Response},
+    Json,
+};
+use axum_extra::{headers::IfNoneMatch, TypedHeader};
+
+use crate::{
+    controllers::themes::{css_response, theme_version_css_url, VERSIONED_CSS_CACHE_CONTROL},
+    error::Error,
+    models::{ThemeVersion, ThemeVersionSummary},
+    AppState,
+};
+
+/// Lists the versions of a theme without their stylesheets, newest first.
+pub async fn list_theme_versions(
+    State(state): State<Arc<AppState>>,
+    Path(theme_id): Path<i64>,
+) -> Result<Json<Vec<ThemeVersionSummary>>, Error> {
+    let mut versions = sqlx::query_as::<_, ThemeVersionSummary>(
+        "SELECT theme_id, version, changelog, min_client_version, created_at FROM theme_versions
+        WHERE theme_id = ?
+        ORDER BY id DESC",
+    )
+    .bind(theme_id)
+    .fetch_all(&state.db)
+    .await?;
+
+    // Every theme has at least one version
+    if versions.is_empty() {
+        return Err(Error::NotFound);
+    }
+
+    for version in &mut versions {
+        version.css_url = theme_version_css_url(version.theme_id, &version.version);
+    }
+
+    Ok(Json(versions))
+}
+
+/// Returns a theme version, or its raw stylesheet for `GET /v2/themes/{id}/versions/{version}.css`.
+/// Versions never change, so their stylesheets are cached for a year.
+pub async fn get_theme_version(
+    State(state): State<Arc<AppState>>,
+    if_none_match: Option<TypedHeader<IfNoneMatch>>,
+    Path((theme_id, version)): Path<(i64, String)>,
+) -> Result<Response, Error> {
+    let (version, is_css) = match version.strip_suffix(".css") {
+        Some(version) => (version, true),
+        None => (version.as_str(), false),
+    };
+
+    let mut theme_version = sqlx::query_as::<_, ThemeVersion>(
+        "SELECT * FROM theme_versions WHERE theme_id = ? AND version = ?",
+    )
+    .bind(theme_id)
+    .bind(version)
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or(Error::NotFound)?;
+
+    if is_css {
+        return css_response(
+            theme_version.css,
+            VERSIONED_CSS_CACHE_CONTROL,
+            if_none_match,
+        );
+    }
+
+    theme_version.css_url = theme_version_css_url(theme_id, &theme_version.version);
+
+    Ok(Json(theme_version).into_response())
+}
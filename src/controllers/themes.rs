@@ -0,0 +1,647 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use axum_extra::{
+    extract::Query,
+    headers::{ETag, IfNoneMatch},
+    TypedHeader,
+};
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::SqliteConnection;
+
+use crate::{
+    auth::{Claims, Permissions},
+    css::{parse_metadata, validate_css, ThemeMetadata},
+    error::Error,
+    models::{Ban, BanScope, Theme, ThemeSummary},
+    utils::deserialize_some,
+    AppState, ENV,
+};
+
+const DEFAULT_THEMES_LIMIT: i64 = 50;
+const MAX_THEMES_LIMIT: i64 = 100;
+
+const MAX_NAME_LENGTH: usize = 100;
+const MAX_DESCRIPTION_LENGTH: usize = 1000;
+
+/// The maximum size of a stylesheet in bytes.
+const MAX_CSS_SIZE: usize = 256 * 1024;
+
+/// The `Cache-Control` of stylesheet URLs pinned to a theme version, which never changes.
+pub(crate) const VERSIONED_CSS_CACHE_CONTROL: &str = "public, max-age=31536000, immutable";
+
+/// The `Cache-Control` of stylesheet URLs that always point to the latest stylesheet.
+const UNVERSIONED_CSS_CACHE_CONTROL: &str = "public, no-cache";
+
+/// The version of newly published themes, if none is given.
+const INITIAL_VERSION: &str = "1.0.0";
+
+/// The public URL the stylesheet of a theme version is served at.
+pub(crate) fn theme_version_css_url(theme_id: i64, version: &str) -> String {
+    format!(
+        "{}/v2/themes/{theme_id}/versions/{version}.css",
+        ENV.public_url
+    )
+}
+
+fn with_derived_fields(mut theme: Theme) -> Theme {
+    theme.css_url = theme_version_css_url(theme.id, &theme.version);
+    theme.metadata = parse_metadata(&theme.css);
+    theme
+}
+
+/// Parses a semantic version, naming `field` in the error.
+fn parse_version(field: &str, version: &str) -> Result<Version, Error> {
+    Version::parse(version).map_err(|error| {
+        Error::BadRequest(format!(
+            "`{field}` is not a valid semantic version: {error}"
+        ))
+    })
+}
+
+/// Serves a stylesheet, for loading it with `@import url(...)` or a `<link>` element.
+///
+/// The global CORS layer allows every origin. `Cross-Origin-Resource-Policy` additionally lets pages
+/// with `Cross-Origin-Embedder-Policy` load the stylesheet.
+pub(crate) fn css_response(
+    css: String,
+    cache_control: &'static str,
+    if_none_match: Option<TypedHeader<IfNoneMatch>>,
+) -> Result<Response, Error> {
+    let etag: ETag = format!("\"{}\"", hex::encode(&Sha256::digest(&css)[..8]))
+        .parse()
+        .map_err(|_| Error::Other(anyhow::anyhow!("invalid stylesheet etag")))?;
+
+    let headers = [
+        (header::CACHE_CONTROL, cache_control),
+        (header::X_CONTENT_TYPE_OPTIONS, "nosniff"),
+        (
+            header::HeaderName::from_static("cross-origin-resource-policy"),
+            "cross-origin",
+        ),
+    ];
+
+    if if_none_match
+        .is_some_and(|TypedHeader(if_none_match)| !if_none_match.precondition_passes(&etag))
+    {
+        return Ok((StatusCode::NOT_MODIFIED, TypedHeader(etag), headers).into_response());
+    }
+
+    Ok((
+        TypedHeader(etag),
+        headers,
+        [(header::CONTENT_TYPE, "text/css; charset=utf-8")],
+        css,
+    )
+        .into_response())
+}
+
+/// Whether the claims belong to the author of a theme, or to someone who can manage all themes.
+fn can_edit_theme(claims: &Claims, theme: &Theme) -> bool {
+    theme.author_id.as_deref() == Some(claims.user_id())
+        || claims.permissions().contains(Permissions::ManageThemes)
+}
+
+fn validate_name(name: &str) -> Result<(), Error> {
+    if name.trim().is_empty() || name.chars().count() > MAX_NAME_LENGTH {
+        return Err(Error::BadRequest(format!(
+            "theme name must be between 1 and {MAX_NAME_LENGTH} characters long"
+        )));
+    }
+
+    Ok(())
+}
+
+fn validate_description(description: Option<&str>) -> Result<(), Error> {
+    if description.is_some_and(|description| description.chars().count() > MAX_DESCRIPTION_LENGTH) {
+        return Err(Error::BadRequest(format!(
+            "theme description must be at most {MAX_DESCRIPTION_LENGTH} characters long"
+        )));
+    }
+
+    Ok(())
+}
+
+fn validate_stylesheet(css: &str) -> Result<(), Error> {
+    if css.len() > MAX_CSS_SIZE {
+        return Err(Error::BadRequest(format!(
+            "stylesheet must not be larger than {MAX_CSS_SIZE} bytes"
+        )));
+    }
+
+    validate_css(css, &ENV.theme_css_allowed_hosts).map_err(Error::InvalidCss)
+}
+
+fn contradicts_header(field: &str) -> Error {
+    Error::BadRequest(format!(
+        "`{field}` does not match `@{field}` in the stylesheet header"
+    ))
+}
+
+/// Takes a field from the stylesheet header if the request omits it, and rejects requests that
+/// contradict the header.
+fn merge_header_field(
+    field: &str,
+    body: Option<String>,
+    header: Option<String>,
+) -> Result<Option<String>, Error> {
+    match (body, header) {
+        (Some(body), Some(header)) if body.split_whitespace().ne(header.split_whitespace()) => {
+            Err(contradicts_header(field))
+        }
+        (body, header) => Ok(body.or(header)),
+    }
+}
+
+/// Takes the version from the stylesheet header if the request omits it. Headers often carry
+/// versions like `1.0` that are not semantic versions; those are ignored when the request has a
+/// version, and only rejected when there is nothing else to use.
+fn merge_header_version(
+    body: Option<&str>,
+    header: Option<&str>,
+) -> Result<Option<Version>, Error> {
+    let body = body
+        .map(|version| parse_version("version", version))
+        .transpose()?;
+    let header = header.map(|version| parse_version("@version", version));
+
+    match (body, header) {
+        (Some(body), Some(Ok(header))) if body != header => Err(contradicts_header("version")),
+        (Some(body), _) => Ok(Some(body)),
+        (None, header) => header.transpose(),
+    }
+}
+
+/// Rejects stylesheet headers claiming an `@authorId` other than the author of the theme.
+fn validate_header_author(metadata: &ThemeMetadata, author_id: Option<&str>) -> Result<(), Error> {
+    match (metadata.author_id.as_deref(), author_id) {
+        (Some(header), Some(author_id)) if header != author_id => Err(Error::BadRequest(
+            "`@authorId` in the stylesheet header does not match the author of the theme".into(),
+        )),
+        _ => Ok(()),
+    }
+}
+
+async fn find_theme(state: &AppState, theme_id: i64) -> Result<Theme, Error> {
+    sqlx::query_as::<_, Theme>("SELECT * FROM themes WHERE id = ?")
+        .bind(theme_id)
+        .fetch_optional(&state.db)
+        .await?
+        .map(with_derived_fields)
+        .ok_or(Error::NotFound)
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ListThemesRequest {
+    author_id: Option<String>,
+    /// Only return themes older than this theme id, for paging through the list.
+    before: Option<i64>,
+    limit: Option<i64>,
+}
+
+/// Lists themes without their stylesheets, newest first.
+pub async fn list_themes(
+    State(state): State<Arc<AppState>>,
+    Query(ListThemesRequest {
+        author_id,
+        before,
+        limit,
+    }): Query<ListThemesRequest>,
+) -> Result<Json<Vec<ThemeSummary>>, Error> {
+    let limit = limit
+        .unwrap_or(DEFAULT_THEMES_LIMIT)
+        .clamp(1, MAX_THEMES_LIMIT);
+
+    let themes = sqlx::query_as::<_, ThemeSummary>(
+        "SELECT id, name, description, author_id, version, created_at, updated_at FROM themes
+        WHERE (?1 IS NULL OR author_id = ?1)
+            AND (?2 IS NULL OR id < ?2)
+        ORDER BY id DESC
+        LIMIT ?3",
+    )
+    .bind(author_id)
+    .bind(before)
+    .bind(limit)
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(Json(themes))
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct GetThemeRequest {
+    /// For stylesheets, serve the newest version supporting this client version instead of the
+    /// latest version.
+    client_version: Option<String>,
+}
+
+/// Returns a theme, or its raw stylesheet for `GET /v2/themes/{id}.css`. Both share a route, since
+/// path parameters cannot have a suffix.
+pub async fn get_theme(
+    State(state): State<Arc<AppState>>,
+    if_none_match: Option<TypedHeader<IfNoneMatch>>,
+    Path(id): Path<String>,
+    Query(GetThemeRequest { client_version }): Query<GetThemeRequest>,
+) -> Result<Response, Error> {
+    let (id, is_css) = match id.strip_suffix(".css") {
+        Some(id) => (id, true),
+        None => (id.as_str(), false),
+    };
+    let theme_id: i64 = id.parse().map_err(|_| Error::NotFound)?;
+
+    if is_css {
+        let css = match client_version {
+            Some(client_version) => find_compatible_css(&state, theme_id, &client_version).await?,
+            None => sqlx::query_scalar!("SELECT css FROM themes WHERE id = ?", theme_id)
+                .fetch_optional(&state.db)
+                .await?
+                .ok_or(Error::NotFound)?,
+        };
+
+        css_response(css, UNVERSIONED_CSS_CACHE_CONTROL, if_none_match)
+    } else {
+        Ok(Json(find_theme(&state, theme_id).await?).into_response())
+    }
+}
+
+/// Returns the stylesheet of the newest theme version whose minimum client version is at most
+/// `client_version`.
+async fn find_compatible_css(
+    state: &AppState,
+    theme_id: i64,
+    client_version: &str,
+) -> Result<String, Error> {
+    let client_version = parse_version("client_version", client_version)?;
+
+    let versions = sqlx::query!(
+        r#"SELECT id AS "id!", min_client_version FROM theme_versions
+        WHERE theme_id = ?
+        ORDER BY id DESC"#,
+        theme_id
+    )
+    .fetch_all(&state.db)
+    .await?;
+
+    let compatible_version_id = versions
+        .into_iter()
+        .find(|version| {
+            version
+                .min_client_version
+                .as_deref()
+                .is_none_or(|min_client_version| {
+                    Version::parse(min_client_version)
+                        .is_ok_and(|min_client_version| min_client_version <= client_version)
+                })
+        })
+        .ok_or(Error::NotFound)?
+        .id;
+
+    let css = sqlx::query_scalar!(
+        "SELECT css FROM theme_versions WHERE id = ?",
+        compatible_version_id
+    )
+    .fetch_one(&state.db)
+    .await?;
+
+    Ok(css)
+}
+
+/// Records the current state of a theme as an immutable version.
+async fn insert_version(
+    conn: &mut SqliteConnection,
+    theme_id: i64,
+    changelog: Option<String>,
+) -> Result<(), Error> {
+    sqlx::query!(
+        "INSERT INTO theme_versions
+            (theme_id, version, name, description, css, changelog, min_client_version)
+        SELECT id, version, name, description, css, ?, min_client_version
+        FROM themes
+        WHERE id = ?",
+        changelog,
+        theme_id
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CreateThemeRequest {
+    /// Taken from `@name` in the stylesheet header if omitted.
+    name: Option<String>,
+    /// Taken from `@description` in the stylesheet header if omitted.
+    description: Option<String>,
+    css: String,
+    /// A semantic version, taken from `@version` in the stylesheet header if omitted, or `1.0.0`.
+    version: Option<String>,
+    changelog: Option<String>,
+    /// The oldest client version this release supports, as a semantic version.
+    min_client_version: Option<String>,
+}
+
+/// Publishes a theme, authored by the requesting user, together with its first version.
+///
+/// Fields missing from the request are filled in from the metadata header of the stylesheet.
+/// Globally banned users cannot publish themes, even with a token issued before their ban.
+pub async fn create_theme(
+    State(state): State<Arc<AppState>>,
+    claims: Claims,
+    Json(body): Json<CreateThemeRequest>,
+) -> Result<Json<Theme>, Error> {
+    if Ban::find_active(&state.db, claims.user_id())
+        .await?
+        .is_some_and(|ban| ban.restricts(BanScope::Global))
+    {
+        return Err(Error::Banned);
+    }
+
+    validate_stylesheet(&body.css)?;
+
+    let metadata = parse_metadata(&body.css).unwrap_or_default();
+    validate_header_author(&metadata, Some(claims.user_id()))?;
+
+    let name = merge_header_field("name", body.name, metadata.name)?.ok_or_else(|| {
+        Error::BadRequest(
+            "`name` is required, either in the request body or as `@name` in the stylesheet header"
+                .into(),
+        )
+    })?;
+    let description = merge_header_field("description", body.description, metadata.description)?;
+
+    validate_name(&name)?;
+    validate_description(description.as_deref())?;
+
+    let version = match merge_header_version(body.version.as_deref(), metadata.version.as_deref())?
+    {
+        Some(version) => version,
+        None => parse_version("version", INITIAL_VERSION)?,
+    };
+    let min_client_version = body
+        .min_client_version
+        .as_deref()
+        .map(|min_client_version| parse_version("min_client_version", min_client_version))
+        .transpose()?;
+
+    let mut tx = state.db.begin().await?;
+
+    let theme = sqlx::query_as::<_, Theme>(
+        "INSERT INTO themes (name, description, author_id, css, version, min_client_version)
+        VALUES (?, ?, ?, ?, ?, ?)
+        RETURNING *",
+    )
+    .bind(name)
+    .bind(description)
+    .bind(claims.user_id())
+    .bind(body.css)
+    .bind(version.to_string())
+    .bind(min_client_version.map(|version| version.to_string()))
+    .fetch_one(&mut *tx)
+    .await?;
+
+    insert_version(&mut tx, theme.id, body.changelog).await?;
+    tx.commit().await?;
+
+    Ok(Json(with_derived_fields(theme)))
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct UpdateThemeRequest {
+    name: Option<String>,
+    /// Set to `null` to remove the description.
+    #[serde(default, deserialize_with = "deserialize_some")]
+    description: Option<Option<String>>,
+    css: Option<String>,
+    /// The semantic version of this release, which must be greater than the current version. Taken
+    /// from `@version` in the header of `css` if omitted.
+    version: Option<String>,
+    changelog: Option<String>,
+    /// The oldest client version this release supports, as a semantic version.
+    min_client_version: Option<String>,
+}
+
+/// Updates a theme, publishing the result as a new version. Only its author and theme managers can
+/// edit it.
+///
+/// If the request contains a stylesheet, fields missing from the request are filled in from its
+/// metadata header.
+pub async fn update_theme(
+    State(state): State<Arc<AppState>>,
+    claims: Claims,
+    Path(theme_id): Path<i64>,
+    Json(body): Json<UpdateThemeRequest>,
+) -> Result<Json<Theme>, Error> {
+    let theme = find_theme(&state, theme_id).await?;
+
+    if !can_edit_theme(&claims, &theme) {
+        return Err(Error::MissingPermissions {
+            missing_permissions: Permissions::ManageThemes,
+        });
+    }
+
+    if Ban::find_active(&state.db, claims.user_id())
+        .await?
+        .is_some_and(|ban| ban.restricts(BanScope::Global))
+    {
+        return Err(Error::Banned);
+    }
+
+    if let Some(css) = &body.css {
+        validate_stylesheet(css)?;
+    }
+
+    let metadata = body
+        .css
+        .as_deref()
+        .and_then(parse_metadata)
+        .unwrap_or_default();
+    validate_header_author(&metadata, theme.author_id.as_deref())?;
+
+    let name = merge_header_field("name", body.name, metadata.name)?;
+    let description = match body.description {
+        Some(None) if metadata.description.is_some() => {
+            return Err(contradicts_header("description"));
+        }
+        Some(None) => Some(None),
+        description => {
+            merge_header_field("description", description.flatten(), metadata.description)?
+                .map(Some)
+        }
+    };
+
+    if let Some(name) = &name {
+        validate_name(name)?;
+    }
+    validate_description(description.as_ref().and_then(Option::as_deref))?;
+
+    let version = merge_header_version(body.version.as_deref(), metadata.version.as_deref())?
+        .ok_or_else(|| {
+            Error::BadRequest(
+                "`version` is required, either in the request body or as `@version` in the stylesheet header"
+                    .into(),
+            )
+        })?;
+    let current_version = parse_version("version", &theme.version)?;
+    if version <= current_version {
+        return Err(Error::BadRequest(format!(
+            "version must be greater than the current version {current_version}"
+        )));
+    }
+
+    let min_client_version = body
+        .min_client_version
+        .as_deref()
+        .map(|min_client_version| parse_version("min_client_version", min_client_version))
+        .transpose()?;
+
+    let update_description = description.is_some();
+
+    let mut tx = state.db.begin().await?;
+
+    let theme = sqlx::query_as::<_, Theme>(
+        "UPDATE themes SET
+            name = COALESCE(?, name),
+            description = CASE WHEN ? THEN ? ELSE description END,
+            css = COALESCE(?, css),
+            version = ?,
+            min_client_version = ?,
+            updated_at = CURRENT_TIMESTAMP
+        WHERE id = ?
+        RETURNING *",
+    )
+    .bind(name)
+    .bind(update_description)
+    .bind(description.flatten())
+    .bind(body.css)
+    .bind(version.to_string())
+    .bind(min_client_version.map(|version| version.to_string()))
+    .bind(theme_id)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or(Error::NotFound)?;
+
+    insert_version(&mut tx, theme.id, body.changelog).await?;
+    tx.commit().await?;
+
+    Ok(Json(with_derived_fields(theme)))
+}
+
+/// Deletes a theme. Only its author and theme managers can delete it.
+pub async fn delete_theme(
+    State(state): State<Arc<AppState>>,
+    claims: Claims,
+    Path(theme_id): Path<i64>,
+) -> Result<(), Error> {
+    let theme = find_theme(&state, theme_id).await?;
+
+    if !can_edit_theme(&claims, &theme) {
+        return Err(Error::MissingPermissions {
+            missing_permissions: Permissions::ManageThemes,
+        });
+    }
+
+    let response = sqlx::query!("DELETE FROM themes WHERE id = ?", theme_id)
+        .execute(&state.db)
+        .await?;
+
+    if response.rows_affected() != 0 {
+        Ok(())
+    } else {
+        Err(Error::NotFound)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn some(value: &str) -> Option<String> {
+        Some(value.to_owned())
+    }
+
+    #[test]
+    fn merges_header_fields() {
+        let cases = [
+            // Only one side is set
+            (None, None, Some(None)),
+            (some("Body"), None, Some(some("Body"))),
+            (None, some("Header"), Some(some("Header"))),
+            // Both agree, ignoring differences in whitespace
+            (some("Same"), some("Same"), Some(some("Same"))),
+            (
+                some(" A  dark\ntheme "),
+                some("A dark theme"),
+                Some(some(" A  dark\ntheme ")),
+            ),
+            // Both disagree
+            (some("Body"), some("Header"), None),
+            (some("name"), some("Name"), None),
+        ];
+
+        for (body, header, expected) in cases {
+            let result = merge_header_field("name", body.clone(), header.clone());
+            assert_eq!(result.ok(), expected, "{body:?} and {header:?}");
+        }
+    }
+
+    #[test]
+    fn reports_contradicting_header_fields() {
+        let error = merge_header_field("name", some("Body"), some("Header")).unwrap_err();
+        assert_eq!(
+            error.into_parts().1,
+            "`name` does not match `@name` in the stylesheet header"
+        );
+    }
+
+    #[test]
+    fn merges_header_versions() {
+        let version = |version: &str| Some(Version::parse(version).unwrap());
+
+        let cases = [
+            (None, None, Some(None)),
+            (Some("1.2.0"), None, Some(version("1.2.0"))),
+            (None, Some("1.2.0"), Some(version("1.2.0"))),
+            (Some("1.2.0"), Some("1.2.0"), Some(version("1.2.0"))),
+            (Some("1.2.0"), Some("1.3.0"), None),
+            // Invalid header versions are only rejected when the body has none
+            (Some("1.2.0"), Some("1.2"), Some(version("1.2.0"))),
+            (None, Some("1.2"), None),
+            (Some("1.2"), None, None),
+            (Some("1.2"), Some("1.2.0"), None),
+        ];
+
+        for (body, header, expected) in cases {
+            let result = merge_header_version(body, header);
+            assert_eq!(result.ok(), expected, "{body:?} and {header:?}");
+        }
+    }
+
+    #[test]
+    fn validates_header_authors() {
+        let metadata = |author_id: Option<&str>| ThemeMetadata {
+            author_id: author_id.map(str::to_owned),
+            ..Default::default()
+        };
+
+        let cases = [
+            (None, Some("1"), true),
+            (Some("1"), Some("1"), true),
+            (Some("1"), Some("2"), false),
+            // Themes without an author cannot be checked
+            (Some("1"), None, true),
+        ];
+
+        for (header, author_id, expected) in cases {
+            let result = validate_header_author(&metadata(header), author_id);
+            assert_eq!(result.is_ok(), expected, "{header:?} and {author_id:?}");
+        }
+    }
+}
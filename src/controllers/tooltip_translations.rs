@@ -0,0 +1,192 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    auth::{require_permissions, Claims, Permissions},
+    controllers::badge_customizations::MAX_TOOLTIP_LENGTH,
+    error::Error,
+    locale::normalize_locale,
+    AppState,
+};
+
+#[derive(Serialize, Deserialize)]
+pub struct SetTooltipTranslationRequest {
+    tooltip: String,
+}
+
+fn validate_translation(locale: &str, tooltip: &str) -> Result<String, Error> {
+    let locale = normalize_locale(locale).ok_or(Error::BadRequest("invalid locale".into()))?;
+
+    if tooltip.is_empty() || tooltip.chars().count() > MAX_TOOLTIP_LENGTH {
+        return Err(Error::BadRequest(format!(
+            "tooltip must be between 1 and {MAX_TOOLTIP_LENGTH} characters long"
+        )));
+    }
+
+    Ok(locale)
+}
+
+/// Lists the tooltip translations of a badge, keyed by their locale.
+pub async fn list_badge_translations(
+    State(state): State<Arc<AppState>>,
+    Path(badge_id): Path<i64>,
+) -> Result<Json<BTreeMap<String, String>>, Error> {
+    let translations = sqlx::query!(
+        "SELECT locale, tooltip FROM badge_tooltip_translations WHERE badge_id = ?",
+        badge_id
+    )
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(Json(
+        translations
+            .into_iter()
+            .map(|translation| (translation.locale, translation.tooltip))
+            .collect(),
+    ))
+}
+
+pub async fn set_badge_translation(
+    State(state): State<Arc<AppState>>,
+    claims: Claims,
+    Path((badge_id, locale)): Path<(i64, String)>,
+    Json(body): Json<SetTooltipTranslationRequest>,
+) -> Result<(), Error> {
+    require_permissions(claims.permissions(), Permissions::ManageBadges)?;
+
+    let locale = validate_translation(&locale, &body.tooltip)?;
+
+    sqlx::query!("SELECT id FROM badges WHERE id = ?", badge_id)
+        .fetch_optional(&state.db)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    sqlx::query!(
+        "INSERT INTO badge_tooltip_translations (badge_id, locale, tooltip) VALUES (?, ?, ?)
+        ON CONFLICT (badge_id, locale) DO UPDATE SET tooltip = excluded.tooltip",
+        badge_id,
+        locale,
+        body.tooltip
+    )
+    .execute(&state.db)
+    .await?;
+
+    state.cache.badges.invalidate();
+    state.cache.translation_locales.invalidate();
+
+    Ok(())
+}
+
+pub async fn delete_badge_translation(
+    State(state): State<Arc<AppState>>,
+    claims: Claims,
+    Path((badge_id, locale)): Path<(i64, String)>,
+) -> Result<(), Error> {
+    require_permissions(claims.permissions(), Permissions::ManageBadges)?;
+
+    let locale = normalize_locale(&locale).ok_or(Error::NotFound)?;
+
+    let response = sqlx::query!(
+        "DELETE FROM badge_tooltip_translations WHERE badge_id = ? AND locale = ?",
+        badge_id,
+        locale
+    )
+    .execute(&state.db)
+    .await?;
+
+    if response.rows_affected() != 0 {
+        state.cache.badges.invalidate();
+        state.cache.translation_locales.invalidate();
+        Ok(())
+    } else {
+        Err(Error::NotFound)
+    }
+}
+
+/// Lists the tooltip translations of a badge definition, keyed by their locale.
+pub async fn list_badge_definition_translations(
+    State(state): State<Arc<AppState>>,
+    Path(definition_id): Path<i64>,
+) -> Result<Json<BTreeMap<String, String>>, Error> {
+    let translations = sqlx::query!(
+        "SELECT locale, tooltip FROM badge_definition_tooltip_translations WHERE definition_id = ?",
+        definition_id
+    )
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(Json(
+        translations
+            .into_iter()
+            .map(|translation| (translation.locale, translation.tooltip))
+            .collect(),
+    ))
+}
+
+/// Sets the translation of a badge definition tooltip. It applies to every badge assigned from the
+/// definition that does not override its tooltip.
+pub async fn set_badge_definition_translation(
+    State(state): State<Arc<AppState>>,
+    claims: Claims,
+    Path((definition_id, locale)): Path<(i64, String)>,
+    Json(body): Json<SetTooltipTranslationRequest>,
+) -> Result<(), Error> {
+    require_permissions(claims.permissions(), Permissions::ManageBadges)?;
+
+    let locale = validate_translation(&locale, &body.tooltip)?;
+
+    sqlx::query!(
+        "SELECT id FROM badge_definitions WHERE id = ?",
+        definition_id
+    )
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or(Error::NotFound)?;
+
+    sqlx::query!(
+        "INSERT INTO badge_definition_tooltip_translations (definition_id, locale, tooltip)
+        VALUES (?, ?, ?)
+        ON CONFLICT (definition_id, locale) DO UPDATE SET tooltip = excluded.tooltip",
+        definition_id,
+        locale,
+        body.tooltip
+    )
+    .execute(&state.db)
+    .await?;
+
+    state.cache.badges.invalidate();
+    state.cache.translation_locales.invalidate();
+
+    Ok(())
+}
+
+pub async fn delete_badge_definition_translation(
+    State(state): State<Arc<AppState>>,
+    claims: Claims,
+    Path((definition_id, locale)): Path<(i64, String)>,
+) -> Result<(), Error> {
+    require_permissions(claims.permissions(), Permissions::ManageBadges)?;
+
+    let locale = normalize_locale(&locale).ok_or(Error::NotFound)?;
+
+    let response = sqlx::query!(
+        "DELETE FROM badge_definition_tooltip_translations WHERE definition_id = ? AND locale = ?",
+        definition_id,
+        locale
+    )
+    .execute(&state.db)
+    .await?;
+
+    if response.rows_affected() != 0 {
+        state.cache.badges.invalidate();
+        state.cache.translation_locales.invalidate();
+        Ok(())
+    } else {
+        Err(Error::NotFound)
+    }
+}
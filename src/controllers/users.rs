@@ -0,0 +1,97 @@
+use std::{collections::HashMap, sync::Arc};
+
+use axum::{extract::State, Json};
+use serde::{Deserialize, Serialize};
+use sqlx::{QueryBuilder, Sqlite};
+
+use crate::{
+    error::Error,
+    models::{Badge, Ban},
+    AppState,
+};
+
+/// The maximum amount of users that can be looked up in a single request.
+pub const MAX_LOOKUP_USERS: usize = 100;
+
+#[derive(Serialize, Deserialize)]
+pub struct LookupUsersRequest {
+    user_ids: Vec<String>,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+pub struct UserLookup {
+    badges: Vec<Badge>,
+    ban: Option<Ban>,
+}
+
+pub async fn lookup_users(
+    State(state): State<Arc<AppState>>,
+    Json(LookupUsersRequest { mut user_ids }): Json<LookupUsersRequest>,
+) -> Result<Json<HashMap<String, UserLookup>>, Error> {
+    user_ids.sort_unstable();
+    user_ids.dedup();
+
+    if user_ids.len() > MAX_LOOKUP_USERS {
+        return Err(Error::BadRequest(format!(
+            "cannot look up more than {MAX_LOOKUP_USERS} users at once"
+        )));
+    }
+
+    let mut lookup: HashMap<String, UserLookup> = user_ids
+        .iter()
+        .map(|user_id| (user_id.clone(), UserLookup::default()))
+        .collect();
+
+    if user_ids.is_empty() {
+        return Ok(Json(lookup));
+    }
+
+    let badges = select_for_users(
+        "SELECT * FROM resolved_badges
+        WHERE NOT hidden
+            AND (expires_at IS NULL OR datetime(expires_at) > datetime('now'))
+            AND user_id IN ",
+        &user_ids,
+        " ORDER BY position, id",
+    )
+    .build_query_as::<Badge>()
+    .fetch_all(&state.db)
+    .await?;
+
+    for badge in badges {
+        if let Some(user) = lookup.get_mut(&badge.user_id) {
+            user.badges.push(badge);
+        }
+    }
+
+    let bans = select_for_users("SELECT * FROM bans WHERE user_id IN ", &user_ids, "")
+        .build_query_as::<Ban>()
+        .fetch_all(&state.db)
+        .await?;
+
+    for ban in bans.into_iter().filter(Ban::is_active) {
+        if let Some(user) = lookup.get_mut(&ban.user_id) {
+            user.ban = Some(ban);
+        }
+    }
+
+    Ok(Json(lookup))
+}
+
+fn select_for_users<'a>(
+    query: &str,
+    user_ids: &'a [String],
+    suffix: &str,
+) -> QueryBuilder<'a, Sqlite> {
+    let mut builder = QueryBuilder::new(query);
+
+    builder.push("(");
+    let mut separated = builder.separated(", ");
+    for user_id in user_ids {
+        separated.push_bind(user_id);
+    }
+    separated.push_unseparated(")");
+    builder.push(suffix);
+
+    builder
+}
@@ -0,0 +1,788 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// The maximum amount of problems reported for a single stylesheet.
+const MAX_REPORTED_ERRORS: usize = 50;
+
+/// Functions whose string arguments are loaded as URLs, besides `@import`.
+const URL_FUNCTIONS: [&str; 4] = ["url", "src", "image-set", "-webkit-image-set"];
+
+/// A problem in a stylesheet, at a 1-based line and column.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CssError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for CssError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+/// Validates a stylesheet, returning every problem found in it.
+///
+/// Comments, strings and `url()`s must be terminated, and brackets must be balanced. Every URL the
+/// stylesheet loads, through `url()`, `@import` or `image-set()`, must be a fragment, a `data:` URL
+/// or an `https` URL on one of `allowed_hosts` or their subdomains, so a theme cannot make requests
+/// that track its users.
+pub fn validate_css(css: &str, allowed_hosts: &[String]) -> Result<(), Vec<CssError>> {
+    let mut validator = Validator {
+        chars: css.chars().collect(),
+        pos: 0,
+        allowed_hosts,
+        blocks: Vec::new(),
+        import_depth: None,
+        errors: Vec::new(),
+    };
+
+    validator.run();
+
+    if validator.errors.is_empty() {
+        Ok(())
+    } else {
+        Err(validator.errors)
+    }
+}
+
+/// An open bracket.
+struct Block {
+    closing: char,
+    start: usize,
+    /// Whether strings directly inside the block are URLs.
+    url_strings: bool,
+}
+
+/// A tokenizer following [CSS Syntax](https://www.w3.org/TR/css-syntax-3/#tokenization) closely
+/// enough to find every URL a browser would load. Positions are indices into `chars`.
+struct Validator<'a> {
+    chars: Vec<char>,
+    pos: usize,
+    allowed_hosts: &'a [String],
+    blocks: Vec<Block>,
+    /// The block depth of an `@import` rule whose URL may still follow.
+    import_depth: Option<usize>,
+    errors: Vec<CssError>,
+}
+
+impl Validator<'_> {
+    fn run(&mut self) {
+        while let Some(c) = self.peek(0) {
+            if self.errors.len() >= MAX_REPORTED_ERRORS {
+                return;
+            }
+
+            let start = self.pos;
+
+            match c {
+                '/' if self.peek(1) == Some('*') => self.consume_comment(),
+                '"' | '\'' => {
+                    if let Some(value) = self.consume_string(c) {
+                        let is_url = self.import_depth == Some(self.blocks.len())
+                            || self.blocks.last().is_some_and(|block| block.url_strings);
+
+                        if is_url {
+                            self.check_url(start, &value);
+                        }
+                    }
+                }
+                '@' if self.starts_ident(1) => {
+                    self.pos += 1;
+                    if self.consume_name().eq_ignore_ascii_case("import") {
+                        self.import_depth = Some(self.blocks.len());
+                    }
+                }
+                '(' | '[' | '{' => {
+                    self.pos += 1;
+                    self.open(c, start, false);
+                }
+                ')' | ']' | '}' => {
+                    self.pos += 1;
+                    self.close(c, start);
+                }
+                ';' => {
+                    self.pos += 1;
+                    if self.import_depth == Some(self.blocks.len()) {
+                        self.import_depth = None;
+                    }
+                }
+                _ if self.starts_ident(0) => {
+                    let name = self.consume_name();
+
+                    if self.peek(0) == Some('(') {
+                        self.pos += 1;
+
+                        if name.eq_ignore_ascii_case("url") && !self.is_quoted_url() {
+                            self.consume_unquoted_url(start);
+                        } else {
+                            let is_url_function = URL_FUNCTIONS
+                                .iter()
+                                .any(|function| name.eq_ignore_ascii_case(function));
+                            self.open('(', start, is_url_function);
+                        }
+                    }
+                }
+                _ => self.pos += 1,
+            }
+        }
+
+        for block in std::mem::take(&mut self.blocks) {
+            let opening = match block.closing {
+                ')' => '(',
+                ']' => '[',
+                _ => '{',
+            };
+            self.error(block.start, format!("`{opening}` is never closed"));
+        }
+    }
+
+    fn peek(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.pos + offset).copied()
+    }
+
+    fn error(&mut self, index: usize, message: String) {
+        if self.errors.len() >= MAX_REPORTED_ERRORS {
+            return;
+        }
+
+        let mut line = 1;
+        let mut column = 1;
+        let mut chars = self.chars[..index].iter().peekable();
+        while let Some(&c) = chars.next() {
+            if is_newline(c) && !(c == '\r' && chars.peek() == Some(&&'\n')) {
+                line += 1;
+                column = 1;
+            } else if c != '\r' {
+                column += 1;
+            }
+        }
+
+        self.errors.push(CssError {
+            line,
+            column,
+            message,
+        });
+    }
+
+    fn open(&mut self, opening: char, start: usize, url_strings: bool) {
+        if opening == '{' && self.import_depth == Some(self.blocks.len()) {
+            self.import_depth = None;
+        }
+
+        let closing = match opening {
+            '(' => ')',
+            '[' => ']',
+            _ => '}',
+        };
+
+        self.blocks.push(Block {
+            closing,
+            start,
+            url_strings,
+        });
+    }
+
+    fn close(&mut self, closing: char, start: usize) {
+        if self.blocks.last().map(|block| block.closing) != Some(closing) {
+            self.error(start, format!("unexpected `{closing}`"));
+            return;
+        }
+
+        self.blocks.pop();
+        if self
+            .import_depth
+            .is_some_and(|depth| depth > self.blocks.len())
+        {
+            self.import_depth = None;
+        }
+    }
+
+    fn consume_comment(&mut self) {
+        let start = self.pos;
+        self.pos += 2;
+
+        while let Some(c) = self.peek(0) {
+            self.pos += 1;
+            if c == '*' && self.peek(0) == Some('/') {
+                self.pos += 1;
+                return;
+            }
+        }
+
+        self.error(start, "comment is never closed".into());
+    }
+
+    /// Consumes a string, returning its value with escapes resolved.
+    fn consume_string(&mut self, quote: char) -> Option<String> {
+        let start = self.pos;
+        let mut value = String::new();
+        self.pos += 1;
+
+        loop {
+            match self.peek(0) {
+                Some(c) if c == quote => {
+                    self.pos += 1;
+                    return Some(value);
+                }
+                None => break,
+                Some(c) if is_newline(c) => break,
+                Some('\\') => match self.peek(1) {
+                    None => self.pos += 1,
+                    Some(c) if is_newline(c) => {
+                        self.pos += if c == '\r' && self.peek(2) == Some('\n') {
+                            3
+                        } else {
+                            2
+                        };
+                    }
+                    Some(_) => value.push(self.consume_escape()),
+                },
+                Some(c) => {
+                    value.push(c);
+                    self.pos += 1;
+                }
+            }
+        }
+
+        self.error(start, "string is never closed".into());
+        None
+    }
+
+    /// Whether the `url(` just consumed is followed by a quoted URL, which makes it a function.
+    fn is_quoted_url(&self) -> bool {
+        let mut offset = 0;
+        while self.peek(offset).is_some_and(is_whitespace) {
+            offset += 1;
+        }
+
+        matches!(self.peek(offset), Some('"' | '\''))
+    }
+
+    fn consume_unquoted_url(&mut self, start: usize) {
+        let mut value = String::new();
+        self.skip_whitespace();
+
+        loop {
+            match self.peek(0) {
+                None => {
+                    self.error(start, "`url(` is never closed".into());
+                    return;
+                }
+                Some(')') => {
+                    self.pos += 1;
+                    break;
+                }
+                Some(c) if is_whitespace(c) => {
+                    self.skip_whitespace();
+                    if self.peek(0) == Some(')') {
+                        self.pos += 1;
+                        break;
+                    }
+
+                    self.error(start, "unquoted URL must not contain whitespace".into());
+                    return self.consume_bad_url();
+                }
+                Some('\\') if self.is_valid_escape(0) => value.push(self.consume_escape()),
+                Some(c @ ('"' | '\'' | '(' | '\\')) => {
+                    self.error(
+                        start,
+                        format!("unquoted URL must not contain `{c}`, quote the URL instead"),
+                    );
+                    return self.consume_bad_url();
+                }
+                Some(c) if c.is_control() => {
+                    self.error(
+                        start,
+                        "unquoted URL must not contain control characters".into(),
+                    );
+                    return self.consume_bad_url();
+                }
+                Some(c) => {
+                    value.push(c);
+                    self.pos += 1;
+                }
+            }
+        }
+
+        self.check_url(start, &value);
+    }
+
+    /// Skips the rest of an invalid `url(`, like browsers do.
+    fn consume_bad_url(&mut self) {
+        while let Some(c) = self.peek(0) {
+            if c == ')' {
+                self.pos += 1;
+                return;
+            }
+
+            if self.is_valid_escape(0) {
+                self.consume_escape();
+            } else {
+                self.pos += 1;
+            }
+        }
+    }
+
+    fn check_url(&mut self, start: usize, url: &str) {
+        if let Err(message) = check_url(url, self.allowed_hosts) {
+            self.error(start, message);
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek(0).is_some_and(is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    fn is_valid_escape(&self, offset: usize) -> bool {
+        self.peek(offset) == Some('\\') && self.peek(offset + 1).is_some_and(|c| !is_newline(c))
+    }
+
+    fn starts_ident(&self, offset: usize) -> bool {
+        match self.peek(offset) {
+            Some('-') => {
+                self.peek(offset + 1)
+                    .is_some_and(|c| c == '-' || is_ident_start(c))
+                    || self.is_valid_escape(offset + 1)
+            }
+            Some('\\') => self.is_valid_escape(offset),
+            Some(c) => is_ident_start(c),
+            None => false,
+        }
+    }
+
+    /// Consumes an identifier, returning it with escapes resolved.
+    fn consume_name(&mut self) -> String {
+        let mut name = String::new();
+
+        loop {
+            match self.peek(0) {
+                Some(c) if is_ident_start(c) || c.is_ascii_digit() || c == '-' => {
+                    name.push(c);
+                    self.pos += 1;
+                }
+                _ if self.is_valid_escape(0) => name.push(self.consume_escape()),
+                _ => return name,
+            }
+        }
+    }
+
+    /// Consumes a valid escape, starting at its backslash.
+    fn consume_escape(&mut self) -> char {
+        self.pos += 1;
+
+        let Some(c) = self.peek(0) else {
+            return char::REPLACEMENT_CHARACTER;
+        };
+        self.pos += 1;
+
+        if !c.is_ascii_hexdigit() {
+            return c;
+        }
+
+        let mut digits = String::from(c);
+        while digits.len() < 6 && self.peek(0).is_some_and(|c| c.is_ascii_hexdigit()) {
+            digits.extend(self.peek(0));
+            self.pos += 1;
+        }
+
+        match self.peek(0) {
+            Some('\r') if self.peek(1) == Some('\n') => self.pos += 2,
+            Some(c) if is_whitespace(c) => self.pos += 1,
+            _ => {}
+        }
+
+        u32::from_str_radix(&digits, 16)
+            .ok()
+            .filter(|&code_point| code_point != 0)
+            .and_then(char::from_u32)
+            .unwrap_or(char::REPLACEMENT_CHARACTER)
+    }
+}
+
+fn is_newline(c: char) -> bool {
+    matches!(c, '\n' | '\r' | '\x0C')
+}
+
+fn is_whitespace(c: char) -> bool {
+    matches!(c, ' ' | '\t') || is_newline(c)
+}
+
+fn is_ident_start(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_' || !c.is_ascii()
+}
+
+/// Checks a URL loaded by a stylesheet, returning why it is not allowed.
+fn check_url(url: &str, allowed_hosts: &[String]) -> Result<(), String> {
+    // Browsers ignore surrounding whitespace, and tabs and newlines anywhere in a URL
+    let url: String = url
+        .trim_matches(|c: char| c <= ' ')
+        .chars()
+        .filter(|c| !matches!(c, '\t' | '\n' | '\r'))
+        .collect::<String>()
+        .replace('\\', "/");
+
+    if url.is_empty() || url.starts_with('#') {
+        return Ok(());
+    }
+
+    let authority = match url.split_once(':') {
+        Some((scheme, rest)) if is_scheme(scheme) => match scheme.to_ascii_lowercase().as_str() {
+            "data" => return Ok(()),
+            // `https:host` is parsed like `https://host`
+            "https" => rest.trim_start_matches('/'),
+            scheme => return Err(format!("`{scheme}:` URLs are not allowed")),
+        },
+        _ => match url.strip_prefix("//") {
+            Some(rest) => rest.trim_start_matches('/'),
+            None => return Err("relative URLs are not allowed, use an `https` URL".into()),
+        },
+    };
+
+    let authority = authority.split(['/', '?', '#']).next().unwrap_or_default();
+    let host = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host);
+    let host = match host.find(']') {
+        Some(end) if host.starts_with('[') => &host[..=end],
+        _ => host.split(':').next().unwrap_or_default(),
+    };
+    let host = host.trim_end_matches('.').to_lowercase();
+
+    // Browsers decode percent-encoded hosts, which would get around the allowlist
+    if host.is_empty() || host.contains('%') {
+        return Err("URL does not have a valid host".into());
+    }
+
+    let is_allowed = allowed_hosts.iter().any(|allowed| {
+        host == *allowed
+            || host
+                .strip_suffix(allowed.as_str())
+                .is_some_and(|subdomain| subdomain.ends_with('.'))
+    });
+
+    if !is_allowed {
+        return Err(format!("loading URLs from `{host}` is not allowed"));
+    }
+
+    Ok(())
+}
+
+fn is_scheme(scheme: &str) -> bool {
+    let mut chars = scheme.chars();
+
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+}
+
+/// Tags that start a new field of a metadata header anywhere in a line. Any other tag only starts
+/// a field at the beginning of a line, so descriptions can mention `@someone`.
+const METADATA_TAGS: [&str; 7] = [
+    "name",
+    "author",
+    "authorId",
+    "version",
+    "description",
+    "website",
+    "source",
+];
+
+/// The metadata of a BetterDiscord-style header, a `/** ... */` comment at the start of a
+/// stylesheet with lines like ` * @name My Theme`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ThemeMetadata {
+    pub name: Option<String>,
+    /// The display name of the author.
+    pub author: Option<String>,
+    /// The Discord account of the author.
+    pub author_id: Option<String>,
+    pub version: Option<String>,
+    pub description: Option<String>,
+    pub website: Option<String>,
+    pub source: Option<String>,
+}
+
+/// Parses the metadata header of a stylesheet, if it has one.
+pub fn parse_metadata(css: &str) -> Option<ThemeMetadata> {
+    // The comment ends at the first `*/`, which may overlap with its opening like in `/**/`
+    let comment = css
+        .trim_start_matches('\u{feff}')
+        .trim_start()
+        .strip_prefix("/*")?;
+    let header = comment[..comment.find("*/")?].strip_prefix('*')?;
+
+    if header.trim().is_empty() {
+        return None;
+    }
+
+    let mut fields: Vec<(&str, Vec<&str>)> = Vec::new();
+    for line in header.lines() {
+        let line = line.trim_start().trim_start_matches('*');
+
+        for (index, word) in line.split_whitespace().enumerate() {
+            let tag = word
+                .strip_prefix('@')
+                .filter(|tag| index == 0 || METADATA_TAGS.contains(tag));
+
+            match (tag, fields.last_mut()) {
+                (Some(tag), _) => fields.push((tag, Vec::new())),
+                (None, Some((_, words))) => words.push(word),
+                (None, None) => {}
+            }
+        }
+    }
+
+    let mut metadata = ThemeMetadata::default();
+    for (tag, words) in fields {
+        let field = match tag {
+            "name" => &mut metadata.name,
+            "author" => &mut metadata.author,
+            "authorId" => &mut metadata.author_id,
+            "version" => &mut metadata.version,
+            "description" => &mut metadata.description,
+            "website" => &mut metadata.website,
+            "source" => &mut metadata.source,
+            _ => continue,
+        };
+
+        if field.is_none() && !words.is_empty() {
+            *field = Some(words.join(" "));
+        }
+    }
+
+    (metadata != ThemeMetadata::default()).then_some(metadata)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn validate(css: &str) -> Result<(), Vec<CssError>> {
+        validate_css(css, &["allowed.com".to_owned()])
+    }
+
+    fn messages(css: &str) -> Vec<String> {
+        validate(css)
+            .err()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|error| error.message)
+            .collect()
+    }
+
+    #[test]
+    fn accepts_allowed_urls() {
+        let cases = [
+            "a { background: url(https://allowed.com/a.png) }",
+            "a { background: url(https://cdn.allowed.com/a.png) }",
+            "a { background: url('https://allowed.com:443/a.png') }",
+            "a { background: url(data:image/png;base64,AAAA) }",
+            "a { filter: url(#shadow) }",
+            "@import url(\"https://allowed.com/theme.css\");",
+            "@import 'https://allowed.com/theme.css';",
+            "a { background: image-set(\"https://allowed.com/a.png\" 1x) }",
+            "a { content: \"https://evil.com\" }",
+            "/* url(https://evil.com) */",
+        ];
+
+        for css in cases {
+            assert_eq!(messages(css), Vec::<String>::new(), "{css}");
+        }
+    }
+
+    #[test]
+    fn rejects_urls_loaded_from_other_hosts() {
+        let cases = [
+            "a { background: url(https://evil.com/a.png) }",
+            "a { background: url(  https://evil.com/a.png  ) }",
+            "a { background: url(\"https://evil.com/a.png\") }",
+            "a { background: URL(https://evil.com/a.png) }",
+            "a { background: u\\rl(https://evil.com/a.png) }",
+            "a { background: \\75 rl(https://evil.com/a.png) }",
+            "a { background: url(https\\3a //evil.com/a.png) }",
+            "@import \"https://evil.com/theme.css\";",
+            "@IMPORT 'https://evil.com/theme.css';",
+            "@\\69mport \"https://evil.com/theme.css\";",
+            "a { background: image-set(\"https://evil.com/a.png\" 1x) }",
+            "a { background: -webkit-image-set('https://evil.com/a.png' 1x) }",
+            "@font-face { src: src(\"https://evil.com/font.woff2\") }",
+            // Hosts that only look like the allowed one
+            "a { background: url(https://allowed.com.evil.com/a.png) }",
+            "a { background: url(https://notallowed.com/a.png) }",
+            "a { background: url(https://allowed@evil.com/a.png) }",
+            "a { background: url(\"https://evil.com\\\\@allowed.com/a.png\") }",
+            "a { background: url(https://evil.com%2fallowed.com/a.png) }",
+            // Protocol relative and slash variations
+            "a { background: url(//evil.com/a.png) }",
+            "a { background: url(\"\\\\\\\\evil.com/a.png\") }",
+            "a { background: url(https:evil.com/a.png) }",
+            "a { background: url(https:///evil.com/a.png) }",
+            "a { background: url(HTTPS://evil.com/a.png) }",
+        ];
+
+        for css in cases {
+            assert_eq!(messages(css).len(), 1, "{css}");
+        }
+    }
+
+    #[test]
+    fn rejects_other_schemes() {
+        assert_eq!(
+            messages("a { background: url(http://allowed.com/a.png) }"),
+            ["`http:` URLs are not allowed"]
+        );
+        assert_eq!(
+            messages("a { background: url(HTTP://allowed.com/a.png) }"),
+            ["`http:` URLs are not allowed"]
+        );
+        assert_eq!(
+            messages("a { background: url('javascript:alert(1)') }"),
+            ["`javascript:` URLs are not allowed"]
+        );
+        assert_eq!(
+            messages("a { background: url(a.png) }"),
+            ["relative URLs are not allowed, use an `https` URL"]
+        );
+        assert!(validate("a { background: url(HTTPS://ALLOWED.COM/a.png) }").is_ok());
+        assert!(validate("a { background: url(DATA:image/png;base64,AAAA) }").is_ok());
+    }
+
+    #[test]
+    fn accepts_nothing_remote_without_allowed_hosts() {
+        let result = validate_css("a { background: url(https://allowed.com/a.png) }", &[]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn rejects_malformed_stylesheets() {
+        let cases = [
+            ("a { color: red;", "`{` is never closed"),
+            ("a { color: red; }}", "unexpected `}`"),
+            ("a { color: red; ] }", "unexpected `]`"),
+            ("a { content: \"open\n}", "string is never closed"),
+            ("a { color: red; } /* open", "comment is never closed"),
+            (
+                "@import url(https://allowed.com/a.css",
+                "`url(` is never closed",
+            ),
+            (
+                "a { background: url(https://allowed.com/a b.png) }",
+                "unquoted URL must not contain whitespace",
+            ),
+        ];
+
+        for (css, message) in cases {
+            assert_eq!(messages(css), [message], "{css}");
+        }
+    }
+
+    #[test]
+    fn reports_positions() {
+        let css = "a {\n  color: red;\r\n  background: url(https://evil.com/a.png);\n}\n\t\"open";
+        let errors = validate(css).unwrap_err();
+
+        let positions: Vec<_> = errors
+            .iter()
+            .map(|error| (error.line, error.column))
+            .collect();
+        assert_eq!(positions, [(3, 15), (5, 2)]);
+        assert_eq!(
+            errors[0].to_string(),
+            "line 3, column 15: loading URLs from `evil.com` is not allowed"
+        );
+    }
+
+    #[test]
+    fn counts_columns_in_characters() {
+        let errors = validate("/* ü */ a { b: url(//evil.com) }").unwrap_err();
+        assert_eq!((errors[0].line, errors[0].column), (1, 16));
+    }
+
+    #[test]
+    fn parses_metadata_headers() {
+        let css = "\u{feff}
+/**
+ * @name Midnight
+ * @author Jane @ Doe
+ * @authorId 123456789012345678
+ * @version 1.2.0
+ * @description A dark theme
+ *   for night owls, ping @jane
+ * @invite abc
+ * @website https://example.com
+ */
+body { color: white; }";
+
+        assert_eq!(
+            parse_metadata(css),
+            Some(ThemeMetadata {
+                name: Some("Midnight".into()),
+                author: Some("Jane @ Doe".into()),
+                author_id: Some("123456789012345678".into()),
+                version: Some("1.2.0".into()),
+                description: Some("A dark theme for night owls, ping @jane".into()),
+                website: Some("https://example.com".into()),
+                source: None,
+            })
+        );
+    }
+
+    #[test]
+    fn parses_single_line_metadata_headers() {
+        let css = "/** @name One Line @author X @version 2.0.0 @description Short */ a {}";
+
+        assert_eq!(
+            parse_metadata(css),
+            Some(ThemeMetadata {
+                name: Some("One Line".into()),
+                author: Some("X".into()),
+                version: Some("2.0.0".into()),
+                description: Some("Short".into()),
+                ..Default::default()
+            })
+        );
+    }
+
+    #[test]
+    fn keeps_the_first_value_of_repeated_tags() {
+        let metadata = parse_metadata("/**\n * @name First\n * @name Second\n */").unwrap();
+        assert_eq!(metadata.name.as_deref(), Some("First"));
+    }
+
+    #[test]
+    fn ignores_stylesheets_without_metadata_headers() {
+        let cases = [
+            "a {}",
+            "",
+            // Not at the start of the stylesheet
+            "a {} /** @name Late */",
+            // Regular comments
+            "/* @name Regular */",
+            // Empty comments must not extend to a later comment
+            "/**/ a {} /* @name Later */",
+            "/***/ a {} /** @name Later */",
+            "/** */ a {} /* @name Later */",
+            // Unterminated
+            "/** @name Open",
+            // Without any known tag
+            "/** Just a comment @invite abc */",
+        ];
+
+        for css in cases {
+            assert_eq!(parse_metadata(css), None, "{css}");
+        }
+    }
+
+    #[test]
+    fn limits_reported_errors() {
+        let css = "a { background: url(https://evil.com/a.png) }\n".repeat(100);
+        assert_eq!(validate(&css).unwrap_err().len(), MAX_REPORTED_ERRORS);
+    }
+}
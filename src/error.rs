@@ -5,7 +5,7 @@ use axum::{
 };
 use serde::Serialize;
 
-use crate::auth::Permissions;
+use crate::{auth::Permissions, css::CssError};
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
@@ -18,6 +18,15 @@ pub enum Error {
     #[error("not found")]
     NotFound,
 
+    #[error("bad request: {0}")]
+    BadRequest(String),
+
+    #[error("invalid stylesheet")]
+    InvalidCss(Vec<CssError>),
+
+    #[error("user is banned")]
+    Banned,
+
     #[error("request error: {0}")]
     Reqwest(#[from] reqwest::Error),
 
@@ -43,17 +52,22 @@ impl From<jsonwebtoken::errors::Error> for Error {
     }
 }
 
-impl IntoResponse for Error {
-    fn into_response(self) -> Response {
-        #[derive(Serialize)]
-        struct ErrorResponse {
-            status: u16,
-            message: String,
-        }
-
-        let (status, message) = match self {
+impl Error {
+    /// Returns the status code and the message shown to clients for this error.
+    pub fn into_parts(self) -> (StatusCode, String) {
+        match self {
             Error::Auth => (StatusCode::UNAUTHORIZED, "Unauthorized".into()),
             Error::NotFound => (StatusCode::NOT_FOUND, "Not Found".into()),
+            Error::BadRequest(message) => (StatusCode::BAD_REQUEST, message),
+            Error::InvalidCss(errors) => {
+                let message = match errors.first() {
+                    Some(error) => format!("invalid stylesheet at {error}"),
+                    None => "invalid stylesheet".into(),
+                };
+
+                (StatusCode::BAD_REQUEST, message)
+            }
+            Error::Banned => (StatusCode::FORBIDDEN, "Banned".into()),
             Error::MissingPermissions { .. } => {
                 (StatusCode::FORBIDDEN, "Missing Permissions".into())
             }
@@ -62,6 +76,14 @@ impl IntoResponse for Error {
                     StatusCode::CONFLICT,
                     "Conflict with another resource".into(),
                 ),
+                Some(db_error)
+                    if db_error.kind() == sqlx::error::ErrorKind::ForeignKeyViolation =>
+                {
+                    (
+                        StatusCode::BAD_REQUEST,
+                        "Referenced resource does not exist".into(),
+                    )
+                }
                 _ => match error {
                     sqlx::Error::RowNotFound => {
                         (StatusCode::NOT_FOUND, "Resource Not Found".to_string())
@@ -84,11 +106,31 @@ impl IntoResponse for Error {
                     "Internal Server Error".to_string(),
                 )
             }
+        }
+    }
+}
+
+impl IntoResponse for Error {
+    fn into_response(self) -> Response {
+        #[derive(Serialize)]
+        struct ErrorResponse {
+            status: u16,
+            message: String,
+            /// Every problem found in an invalid stylesheet.
+            #[serde(skip_serializing_if = "Vec::is_empty")]
+            errors: Vec<CssError>,
+        }
+
+        let errors = match &self {
+            Error::InvalidCss(errors) => errors.clone(),
+            _ => Vec::new(),
         };
+        let (status, message) = self.into_parts();
 
         let error_response = ErrorResponse {
             message,
             status: status.into(),
+            errors,
         };
 
         (status, Json(error_response)).into_response()
@@ -0,0 +1,107 @@
+use std::collections::HashMap;
+
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+
+use crate::{error::Error, models::Badge};
+
+/// The output formats of the badge list.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BadgeListFormat {
+    /// An array of all badges.
+    #[default]
+    Array,
+    /// A map of user IDs to their badges.
+    Object,
+    /// A map of user IDs to their badges, with only the fields needed to render them.
+    Compact,
+    /// A CSV export of all badges, for staff.
+    Csv,
+}
+
+#[derive(Serialize)]
+struct CompactBadge<'a> {
+    tooltip: &'a str,
+    badge: &'a str,
+}
+
+impl BadgeListFormat {
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::Array | Self::Object | Self::Compact => "application/json",
+            Self::Csv => "text/csv; charset=utf-8",
+        }
+    }
+
+    pub fn render(self, badges: &[Badge]) -> Result<Vec<u8>, Error> {
+        let body = match self {
+            Self::Array => serde_json::to_vec(badges),
+            Self::Object => serde_json::to_vec(&group_by_user(badges, |badge| badge)),
+            Self::Compact => serde_json::to_vec(&group_by_user(badges, |badge| CompactBadge {
+                tooltip: &badge.tooltip,
+                badge: &badge.badge,
+            })),
+            Self::Csv => return Ok(render_csv(badges).into_bytes()),
+        };
+
+        body.map_err(|error| Error::Other(anyhow!(error)))
+    }
+}
+
+fn group_by_user<'a, T>(
+    badges: &'a [Badge],
+    map: impl Fn(&'a Badge) -> T,
+) -> HashMap<&'a str, Vec<T>> {
+    let mut object = HashMap::<&str, Vec<T>>::new();
+    for badge in badges {
+        object.entry(&badge.user_id).or_default().push(map(badge));
+    }
+    object
+}
+
+fn render_csv(badges: &[Badge]) -> String {
+    let mut csv =
+        String::from("id,user_id,definition_id,badge,tooltip,badge_type,granted_at,expires_at\n");
+
+    for badge in badges {
+        let fields = [
+            badge.id.to_string(),
+            badge.user_id.clone(),
+            badge
+                .definition_id
+                .map(|id| id.to_string())
+                .unwrap_or_default(),
+            badge.badge.clone(),
+            badge.tooltip.clone(),
+            badge.badge_type.as_str().to_owned(),
+            badge.granted_at.to_rfc3339(),
+            badge
+                .expires_at
+                .map(|expires_at| expires_at.to_rfc3339())
+                .unwrap_or_default(),
+        ];
+
+        let row: Vec<String> = fields.iter().map(|field| escape_csv(field)).collect();
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+
+    csv
+}
+
+/// Escapes a CSV field. Fields that spreadsheets would run as a formula are prefixed with `'`, since
+/// users control tooltips.
+fn escape_csv(field: &str) -> String {
+    let field = if field.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        format!("'{field}")
+    } else {
+        field.to_owned()
+    };
+
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field
+    }
+}
@@ -0,0 +1,187 @@
+use std::{io::Cursor, path::PathBuf};
+
+use anyhow::anyhow;
+use image::{imageops::FilterType, ImageFormat, ImageReader, Limits};
+use reqwest::Url;
+use sha2::{Digest, Sha256};
+
+use crate::{error::Error, ENV};
+
+/// The maximum size of an uploaded badge image in bytes.
+pub const MAX_BADGE_IMAGE_SIZE: usize = 1024 * 1024;
+
+/// The maximum width and height of an uploaded badge image in pixels.
+pub const MAX_BADGE_IMAGE_DIMENSIONS: u32 = 512;
+
+/// The sizes badge images are resized to after uploading.
+pub const BADGE_IMAGE_SIZES: [u32; 4] = [16, 32, 64, 128];
+
+const ALLOWED_FORMATS: [ImageFormat; 3] = [ImageFormat::Png, ImageFormat::Gif, ImageFormat::WebP];
+
+fn badge_image_dir(badge_id: i64) -> PathBuf {
+    PathBuf::from(&ENV.storage_path)
+        .join("badges")
+        .join(badge_id.to_string())
+}
+
+fn badge_image_path(badge_id: i64, size: Option<u32>) -> PathBuf {
+    let file_name = match size {
+        Some(size) => format!("{size}.png"),
+        None => "original".to_owned(),
+    };
+
+    badge_image_dir(badge_id).join(file_name)
+}
+
+/// The public URL a badge image is served at. `version` changes whenever the image is replaced, so
+/// clients do not keep showing a cached old image.
+pub fn badge_image_url(badge_id: i64, version: &str) -> String {
+    format!("{}/v2/badges/{badge_id}/image?v={version}", ENV.public_url)
+}
+
+/// Whether a badge holder may use `url` as the image of their badge: an image served by this API,
+/// or an `https` URL on one of `BADGE_IMAGE_ALLOWED_HOSTS` or their subdomains.
+pub fn is_allowed_badge_url(url: &str) -> bool {
+    let Ok(url) = Url::parse(url) else {
+        return false;
+    };
+
+    let is_own_image = Url::parse(&ENV.public_url).is_ok_and(|public_url| {
+        url.origin() == public_url.origin()
+            && url
+                .path()
+                .strip_prefix(public_url.path().trim_end_matches('/'))
+                .and_then(|path| path.strip_prefix("/v2/badges/"))
+                .and_then(|path| path.strip_suffix("/image"))
+                .is_some_and(|id| !id.is_empty() && id.bytes().all(|c| c.is_ascii_digit()))
+    });
+
+    let is_allowed_host = url.scheme() == "https"
+        && url.host_str().is_some_and(|host| {
+            let host = host.trim_end_matches('.');
+            ENV.badge_image_allowed_hosts.iter().any(|allowed| {
+                host == allowed
+                    || host
+                        .strip_suffix(allowed.as_str())
+                        .is_some_and(|subdomain| subdomain.ends_with('.'))
+            })
+        });
+
+    is_own_image || is_allowed_host
+}
+
+/// A short hash of image data, used as its version and `ETag`.
+pub fn image_hash(data: &[u8]) -> String {
+    hex::encode(&Sha256::digest(data)[..8])
+}
+
+/// Validates an uploaded badge image, then stores it together with all of its resized variants.
+/// Returns the version of the stored image.
+pub async fn store_badge_image(badge_id: i64, data: Vec<u8>) -> Result<String, Error> {
+    if data.len() > MAX_BADGE_IMAGE_SIZE {
+        return Err(Error::BadRequest(format!(
+            "image must not be larger than {MAX_BADGE_IMAGE_SIZE} bytes"
+        )));
+    }
+
+    let format = image::guess_format(&data)
+        .ok()
+        .filter(|format| ALLOWED_FORMATS.contains(format))
+        .ok_or_else(|| Error::BadRequest("image must be a PNG, GIF or WebP file".into()))?;
+
+    let (original, resized) = tokio::task::spawn_blocking(move || {
+        let invalid_image = |error| Error::BadRequest(format!("invalid image: {error}"));
+        let too_large = || {
+            Error::BadRequest(format!(
+                "image must not be larger than {MAX_BADGE_IMAGE_DIMENSIONS}x{MAX_BADGE_IMAGE_DIMENSIONS} pixels"
+            ))
+        };
+
+        // Read the declared dimensions before decoding, so a small file claiming huge dimensions
+        // cannot make the decoder allocate them
+        let (width, height) = ImageReader::with_format(Cursor::new(&data), format)
+            .into_dimensions()
+            .map_err(invalid_image)?;
+        if width > MAX_BADGE_IMAGE_DIMENSIONS || height > MAX_BADGE_IMAGE_DIMENSIONS {
+            return Err(too_large());
+        }
+
+        let mut limits = Limits::default();
+        limits.max_image_width = Some(MAX_BADGE_IMAGE_DIMENSIONS);
+        limits.max_image_height = Some(MAX_BADGE_IMAGE_DIMENSIONS);
+
+        let mut reader = ImageReader::with_format(Cursor::new(&data), format);
+        reader.limits(limits);
+        let image = reader.decode().map_err(|error| match error {
+            image::ImageError::Limits(_) => too_large(),
+            error => invalid_image(error),
+        })?;
+
+        let mut resized = Vec::with_capacity(BADGE_IMAGE_SIZES.len());
+        for size in BADGE_IMAGE_SIZES {
+            let mut buffer = Cursor::new(Vec::new());
+            image
+                .resize(size, size, FilterType::Lanczos3)
+                .write_to(&mut buffer, ImageFormat::Png)
+                .map_err(|error| Error::Other(anyhow!(error)))?;
+            resized.push((size, buffer.into_inner()));
+        }
+
+        Ok((data, resized))
+    })
+    .await
+    .map_err(|error| Error::Other(anyhow!(error)))??;
+
+    tokio::fs::create_dir_all(badge_image_dir(badge_id))
+        .await
+        .map_err(|error| Error::Other(anyhow!(error)))?;
+
+    let version = image_hash(&original);
+
+    tokio::fs::write(badge_image_path(badge_id, None), original)
+        .await
+        .map_err(|error| Error::Other(anyhow!(error)))?;
+
+    for (size, data) in resized {
+        tokio::fs::write(badge_image_path(badge_id, Some(size)), data)
+            .await
+            .map_err(|error| Error::Other(anyhow!(error)))?;
+    }
+
+    Ok(version)
+}
+
+/// Loads a stored badge image, returning its content type and data.
+///
+/// If `size` is specified, it must be one of [`BADGE_IMAGE_SIZES`].
+pub async fn load_badge_image(
+    badge_id: i64,
+    size: Option<u32>,
+) -> Result<(&'static str, Vec<u8>), Error> {
+    if size.is_some_and(|size| !BADGE_IMAGE_SIZES.contains(&size)) {
+        return Err(Error::BadRequest(format!(
+            "size must be one of {BADGE_IMAGE_SIZES:?}"
+        )));
+    }
+
+    let data = match tokio::fs::read(badge_image_path(badge_id, size)).await {
+        Ok(data) => data,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Err(Error::NotFound),
+        Err(error) => return Err(Error::Other(anyhow!(error))),
+    };
+
+    let content_type = image::guess_format(&data)
+        .map(|format| format.to_mime_type())
+        .unwrap_or("application/octet-stream");
+
+    Ok((content_type, data))
+}
+
+/// Removes a stored badge image and all of its resized variants, if there are any.
+pub async fn delete_badge_image(badge_id: i64) -> Result<(), Error> {
+    match tokio::fs::remove_dir_all(badge_image_dir(badge_id)).await {
+        Ok(()) => Ok(()),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(error) => Err(Error::Other(anyhow!(error))),
+    }
+}
@@ -0,0 +1,256 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::Arc,
+    time::Duration,
+};
+
+use anyhow::anyhow;
+use axum::http::{header, HeaderMap};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    audit,
+    controllers::badges::{insert_badge, CreateBadgeRequest},
+    error::Error,
+    images,
+    models::BadgeType,
+    AppState, ENV,
+};
+
+pub const INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// The maximum amount of contributor pages fetched, at 100 contributors per page.
+const MAX_PAGES: usize = 50;
+
+/// The actor recorded in the audit log for scheduled syncs.
+const AUDIT_ACTOR: &str = "job:contributor_sync";
+
+#[derive(Deserialize)]
+struct Contributor {
+    login: String,
+    #[serde(rename = "type", default)]
+    account_type: Option<String>,
+}
+
+/// What a contributor sync changed, or would change for a dry run.
+#[derive(Serialize, Deserialize, Default)]
+pub struct ContributorSyncReport {
+    pub dry_run: bool,
+    /// The amount of contributors returned by the forge, excluding bots.
+    pub contributors: usize,
+    /// Contributors without a linked Discord account.
+    pub unlinked: Vec<String>,
+    /// Users who were granted a contributor badge.
+    pub granted: Vec<String>,
+    /// Users whose contributor badge was removed.
+    pub revoked: Vec<String>,
+    /// Linked users who could not be granted a badge, because they are banned.
+    pub skipped: Vec<String>,
+}
+
+impl ContributorSyncReport {
+    fn has_changes(&self) -> bool {
+        !self.granted.is_empty() || !self.revoked.is_empty()
+    }
+}
+
+/// Syncs contributor badges on a schedule.
+pub async fn run(state: Arc<AppState>) -> Result<(), Error> {
+    let report = sync(&state, AUDIT_ACTOR, false).await?;
+
+    if report.has_changes() {
+        tracing::debug!(
+            granted = report.granted.len(),
+            revoked = report.revoked.len(),
+            "synced contributor badges"
+        );
+    }
+
+    Ok(())
+}
+
+/// Grants the badge from `CONTRIBUTOR_BADGE_DEFINITION_ID` to every linked contributor returned by
+/// `CONTRIBUTORS_URL`, and removes it from everyone else.
+///
+/// A dry run performs the same changes in a transaction that is rolled back, so the report is
+/// exactly what a real sync would do.
+pub async fn sync(
+    state: &AppState,
+    actor_id: &str,
+    dry_run: bool,
+) -> Result<ContributorSyncReport, Error> {
+    let (Some(url), Some(definition_id)) = (
+        ENV.contributors_url.as_deref(),
+        ENV.contributor_badge_definition_id,
+    ) else {
+        return Err(Error::NotFound);
+    };
+
+    let logins = fetch_contributors(&state.http, url).await?;
+
+    let mut report = ContributorSyncReport {
+        dry_run,
+        contributors: logins.len(),
+        ..Default::default()
+    };
+
+    let mut tx = state.db.begin().await?;
+
+    let links: BTreeMap<String, String> =
+        sqlx::query!("SELECT login, user_id FROM contributor_links")
+            .fetch_all(&mut *tx)
+            .await?
+            .into_iter()
+            .map(|link| (link.login.to_lowercase(), link.user_id))
+            .collect();
+
+    let mut contributor_user_ids = BTreeSet::new();
+    for login in logins {
+        match links.get(&login.to_lowercase()) {
+            Some(user_id) => {
+                contributor_user_ids.insert(user_id.clone());
+            }
+            None => report.unlinked.push(login),
+        }
+    }
+
+    let badges = sqlx::query!(
+        r#"SELECT id AS "id!", user_id FROM badges WHERE definition_id = ?"#,
+        definition_id
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+
+    let badge_holders: BTreeSet<&str> = badges.iter().map(|badge| badge.user_id.as_str()).collect();
+
+    for user_id in &contributor_user_ids {
+        if badge_holders.contains(user_id.as_str()) {
+            continue;
+        }
+
+        let body = CreateBadgeRequest {
+            user_id: user_id.clone(),
+            definition_id: Some(definition_id),
+            tooltip: None,
+            badge: None,
+            badge_type: Some(BadgeType::Contributor),
+            expires_at: None,
+        };
+
+        match insert_badge(&mut tx, body).await {
+            Ok(_) => report.granted.push(user_id.clone()),
+            Err(Error::Banned) => report.skipped.push(user_id.clone()),
+            Err(error) => return Err(error),
+        }
+    }
+
+    // An empty list is far more likely a forge outage or a token lacking access than a repository
+    // without contributors, so it must not remove everyone's badge
+    let skip_revocation = report.contributors == 0;
+    if skip_revocation {
+        tracing::warn!("forge returned no contributors, not removing any contributor badges");
+    }
+
+    let mut deleted_badge_ids = Vec::new();
+    for badge in &badges {
+        if skip_revocation || contributor_user_ids.contains(&badge.user_id) {
+            continue;
+        }
+
+        sqlx::query!("DELETE FROM badges WHERE id = ?", badge.id)
+            .execute(&mut *tx)
+            .await?;
+
+        deleted_badge_ids.push(badge.id);
+        report.revoked.push(badge.user_id.clone());
+    }
+
+    if dry_run || !report.has_changes() {
+        tx.rollback().await?;
+        return Ok(report);
+    }
+
+    audit::record(&mut tx, actor_id, "contributors.sync", &report).await?;
+    tx.commit().await?;
+
+    state.cache.badges.invalidate();
+
+    for badge_id in deleted_badge_ids {
+        images::delete_badge_image(badge_id).await?;
+    }
+
+    Ok(report)
+}
+
+/// Fetches the logins of all contributors, following the pagination `Link` headers of the forge.
+///
+/// The token is only sent to the origin of `url`, so a `Link` header pointing elsewhere cannot
+/// capture it.
+async fn fetch_contributors(http: &reqwest::Client, url: &str) -> Result<Vec<String>, Error> {
+    let base = Url::parse(url).map_err(|error| Error::Other(anyhow!(error)))?;
+
+    let mut logins = Vec::new();
+    let mut page_url = base.clone();
+    page_url.query_pairs_mut().append_pair("per_page", "100");
+
+    for _ in 0..MAX_PAGES {
+        let mut request = http.get(page_url.clone());
+
+        if let Some(token) = &ENV.contributors_token {
+            if is_same_origin(&page_url, &base) {
+                request = request.bearer_auth(token);
+            }
+        }
+
+        let response = request.send().await?.error_for_status()?;
+        let next_page = next_page_url(response.headers());
+
+        let contributors = response.json::<Vec<Contributor>>().await?;
+        logins.extend(
+            contributors
+                .into_iter()
+                .filter(|contributor| contributor.account_type.as_deref() != Some("Bot"))
+                .map(|contributor| contributor.login),
+        );
+
+        match next_page {
+            Some(next_page) => {
+                page_url = base
+                    .join(&next_page)
+                    .map_err(|error| Error::Other(anyhow!(error)))?;
+            }
+            None => return Ok(logins),
+        }
+    }
+
+    // Syncing a partial list would remove the badges of everyone on the remaining pages
+    Err(Error::Other(anyhow!(
+        "contributor list exceeds {MAX_PAGES} pages"
+    )))
+}
+
+fn is_same_origin(url: &Url, base: &Url) -> bool {
+    url.scheme() == base.scheme()
+        && url.host_str() == base.host_str()
+        && url.port_or_known_default() == base.port_or_known_default()
+}
+
+/// Returns the `rel="next"` URL of a `Link` header.
+fn next_page_url(headers: &HeaderMap) -> Option<String> {
+    let link = headers.get(header::LINK)?.to_str().ok()?;
+
+    link.split(',').find_map(|link| {
+        let (url, params) = link.split_once(';')?;
+        let is_next = params
+            .split(';')
+            .any(|param| param.trim() == r#"rel="next""#);
+
+        is_next.then(|| {
+            url.trim()
+                .trim_start_matches('<')
+                .trim_end_matches('>')
+                .to_owned()
+        })
+    })
+}
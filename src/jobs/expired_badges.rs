@@ -0,0 +1,33 @@
+use std::{sync::Arc, time::Duration};
+
+use crate::{error::Error, AppState};
+
+pub const INTERVAL: Duration = Duration::from_secs(60 * 10);
+
+/// Moves all expired badges into the `archived_badges` table.
+pub async fn run(state: Arc<AppState>) -> Result<(), Error> {
+    let mut tx = state.db.begin().await?;
+
+    sqlx::query!(
+        "INSERT INTO archived_badges
+            (id, user_id, definition_id, badge, tooltip, badge_type, granted_at, expires_at)
+        SELECT id, user_id, definition_id, badge, tooltip, badge_type, granted_at, expires_at
+        FROM badges
+        WHERE datetime(expires_at) <= datetime('now')"
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    let response = sqlx::query!("DELETE FROM badges WHERE datetime(expires_at) <= datetime('now')")
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+
+    if response.rows_affected() != 0 {
+        state.cache.badges.invalidate();
+        tracing::debug!(count = response.rows_affected(), "archived expired badges");
+    }
+
+    Ok(())
+}
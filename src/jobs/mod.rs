@@ -0,0 +1,48 @@
+use std::{future::Future, sync::Arc, time::Duration};
+
+use tracing::Instrument;
+
+use crate::{error::Error, AppState, ENV};
+
+pub mod contributor_sync;
+pub mod expired_badges;
+
+/// Spawns all background jobs.
+pub fn spawn_all(state: &Arc<AppState>) {
+    spawn_interval(
+        "expired_badges",
+        state.clone(),
+        expired_badges::INTERVAL,
+        expired_badges::run,
+    );
+
+    if ENV.contributors_url.is_some() && ENV.contributor_badge_definition_id.is_some() {
+        spawn_interval(
+            "contributor_sync",
+            state.clone(),
+            contributor_sync::INTERVAL,
+            contributor_sync::run,
+        );
+    }
+}
+
+/// Runs a job immediately and then every `interval`, logging any errors it returns.
+fn spawn_interval<F, Fut>(name: &'static str, state: Arc<AppState>, interval: Duration, job: F)
+where
+    F: Fn(Arc<AppState>) -> Fut + Send + 'static,
+    Fut: Future<Output = Result<(), Error>> + Send,
+{
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(interval);
+
+        loop {
+            interval.tick().await;
+
+            let span = tracing::debug_span!("job", name);
+
+            if let Err(error) = job(state.clone()).instrument(span.clone()).await {
+                span.in_scope(|| tracing::error!(%error, "job failed"));
+            }
+        }
+    });
+}
@@ -5,17 +5,27 @@ use std::{
 };
 
 use anyhow::anyhow;
+use cache::Cache;
 use error::{Error, Result};
+use models::BadgeType;
 use sqlx::{
     sqlite::{SqliteConnectOptions, SqlitePoolOptions},
     SqlitePool,
 };
 
+pub mod audit;
 pub mod auth;
+pub mod cache;
 pub mod controllers;
+pub mod css;
 pub mod error;
+pub mod formats;
+pub mod images;
+pub mod jobs;
+pub mod locale;
 pub mod models;
 pub mod socket;
+pub mod utils;
 
 #[derive(Debug)]
 pub struct Env {
@@ -25,6 +35,19 @@ pub struct Env {
     pub jwt_secret: String,
     pub database_url: String,
     pub database_create: bool,
+    pub storage_path: String,
+    pub public_url: String,
+    pub badges_cache_control: String,
+    pub self_service_badge_types: Vec<BadgeType>,
+    pub badge_review_required: bool,
+    pub donations_webhook_secret: Option<String>,
+    pub donor_badge_definition_id: Option<i64>,
+    pub donor_badge_days: i64,
+    pub contributors_url: Option<String>,
+    pub contributors_token: Option<String>,
+    pub contributor_badge_definition_id: Option<i64>,
+    pub theme_css_allowed_hosts: Vec<String>,
+    pub badge_image_allowed_hosts: Vec<String>,
 }
 
 pub static ENV: LazyLock<Env> = LazyLock::new(|| {
@@ -42,6 +65,61 @@ pub static ENV: LazyLock<Env> = LazyLock::new(|| {
             .unwrap_or("false".to_owned())
             .parse()
             .expect("Invalid boolean value for environment variable `DATABASE_CREATE` (must be `true` or `false`)"),
+        storage_path: std::env::var("STORAGE_PATH").unwrap_or("data/storage".to_owned()),
+        public_url: std::env::var("PUBLIC_URL")
+            .unwrap_or_default()
+            .trim_end_matches('/')
+            .to_owned(),
+        badges_cache_control: std::env::var("BADGES_CACHE_CONTROL")
+            .unwrap_or("public, no-cache".to_owned()),
+        self_service_badge_types: std::env::var("SELF_SERVICE_BADGE_TYPES")
+            .unwrap_or("donor".to_owned())
+            .split(',')
+            .filter(|badge_type| !badge_type.is_empty())
+            .map(|badge_type| badge_type.trim().parse())
+            .collect::<std::result::Result<_, _>>()
+            .expect("Invalid badge type in environment variable `SELF_SERVICE_BADGE_TYPES`"),
+        badge_review_required: std::env::var("BADGE_REVIEW_REQUIRED")
+            .unwrap_or("true".to_owned())
+            .parse()
+            .expect("Invalid boolean value for environment variable `BADGE_REVIEW_REQUIRED` (must be `true` or `false`)"),
+        donations_webhook_secret: std::env::var("DONATIONS_WEBHOOK_SECRET")
+            .ok()
+            .filter(|secret| !secret.is_empty()),
+        donor_badge_definition_id: std::env::var("DONOR_BADGE_DEFINITION_ID")
+            .ok()
+            .filter(|id| !id.is_empty())
+            .map(|id| id.parse())
+            .transpose()
+            .expect("Invalid integer value for environment variable `DONOR_BADGE_DEFINITION_ID`"),
+        donor_badge_days: std::env::var("DONOR_BADGE_DAYS")
+            .unwrap_or("31".to_owned())
+            .parse()
+            .expect("Invalid integer value for environment variable `DONOR_BADGE_DAYS`"),
+        contributors_url: std::env::var("CONTRIBUTORS_URL")
+            .ok()
+            .filter(|url| !url.is_empty()),
+        contributors_token: std::env::var("CONTRIBUTORS_TOKEN")
+            .ok()
+            .filter(|token| !token.is_empty()),
+        contributor_badge_definition_id: std::env::var("CONTRIBUTOR_BADGE_DEFINITION_ID")
+            .ok()
+            .filter(|id| !id.is_empty())
+            .map(|id| id.parse())
+            .transpose()
+            .expect("Invalid integer value for environment variable `CONTRIBUTOR_BADGE_DEFINITION_ID`"),
+        theme_css_allowed_hosts: std::env::var("THEME_CSS_ALLOWED_HOSTS")
+            .unwrap_or_default()
+            .split(',')
+            .map(|host| host.trim().trim_end_matches('.').to_lowercase())
+            .filter(|host| !host.is_empty())
+            .collect(),
+        badge_image_allowed_hosts: std::env::var("BADGE_IMAGE_ALLOWED_HOSTS")
+            .unwrap_or_default()
+            .split(',')
+            .map(|host| host.trim().trim_end_matches('.').to_lowercase())
+            .filter(|host| !host.is_empty())
+            .collect(),
     };
 
     tracing::debug!("lazily initialized environment");
@@ -52,6 +130,7 @@ pub static ENV: LazyLock<Env> = LazyLock::new(|| {
 pub struct AppState {
     pub db: SqlitePool,
     pub http: reqwest::Client,
+    pub cache: Cache,
 }
 
 impl AppState {
@@ -65,10 +144,19 @@ impl AppState {
             .map_err(|error| Error::Other(anyhow!(error)))?;
 
         let http = reqwest::ClientBuilder::new()
+            .user_agent(concat!(
+                env!("CARGO_PKG_NAME"),
+                "/",
+                env!("CARGO_PKG_VERSION")
+            ))
             .build()
             .map_err(|error| Error::Other(anyhow!(error)))?;
 
-        let state = AppState { db, http };
+        let state = AppState {
+            db,
+            http,
+            cache: Cache::default(),
+        };
 
         Ok(Arc::new(state))
     }
@@ -0,0 +1,284 @@
+use std::{collections::HashMap, sync::Arc};
+
+use axum::http::{header, HeaderMap};
+use sqlx::{QueryBuilder, Sqlite, SqlitePool};
+
+use crate::{error::Error, models::Badge, AppState};
+
+/// The maximum amount of locales taken from a request, before adding their base languages.
+const MAX_REQUESTED_LOCALES: usize = 3;
+
+/// The maximum amount of badges whose translations are loaded with a single query, to stay below
+/// the SQLite parameter limit.
+const MAX_BADGES_PER_QUERY: usize = 1000;
+
+/// Normalizes a locale tag like `en-US` to `en-us`, or returns `None` if it is not a valid tag.
+pub fn normalize_locale(locale: &str) -> Option<String> {
+    let locale = locale.trim().to_ascii_lowercase();
+
+    let is_valid = !locale.is_empty()
+        && locale.len() <= 35
+        && locale
+            .split('-')
+            .all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_alphanumeric()));
+
+    is_valid.then_some(locale)
+}
+
+/// Returns the locales preferred by a request, most preferred first.
+///
+/// The `locale` query parameter takes precedence over the `Accept-Language` header. Every locale
+/// is followed by its base language, so `en-US` falls back to `en`.
+pub fn preferred_locales(locale: Option<&str>, headers: &HeaderMap) -> Vec<String> {
+    let requested: Vec<String> = match locale {
+        Some(locale) => normalize_locale(locale).into_iter().collect(),
+        None => headers
+            .get(header::ACCEPT_LANGUAGE)
+            .and_then(|value| value.to_str().ok())
+            .map(parse_accept_language)
+            .unwrap_or_default(),
+    };
+
+    let mut locales = Vec::new();
+    for locale in requested.into_iter().take(MAX_REQUESTED_LOCALES) {
+        let base = locale.split('-').next().map(str::to_owned);
+
+        for locale in [Some(locale), base].into_iter().flatten() {
+            if !locales.contains(&locale) {
+                locales.push(locale);
+            }
+        }
+    }
+
+    locales
+}
+
+/// Returns the locales of a request that have at least one translation, in order of preference.
+///
+/// Responses only depend on these locales, so caching by them keeps the amount of cache entries
+/// bounded by the translations that exist, rather than by what clients send.
+pub async fn resolve_locales(state: &AppState, locales: Vec<String>) -> Result<Vec<String>, Error> {
+    if locales.is_empty() {
+        return Ok(locales);
+    }
+
+    let available = match state.cache.translation_locales.get(&()) {
+        Some(available) => available,
+        None => {
+            let generation = state.cache.translation_locales.generation();
+            let available: Arc<[String]> = sqlx::query_scalar!(
+                "SELECT locale FROM badge_tooltip_translations
+                UNION
+                SELECT locale FROM badge_definition_tooltip_translations"
+            )
+            .fetch_all(&state.db)
+            .await?
+            .into();
+
+            state
+                .cache
+                .translation_locales
+                .insert(generation, (), available.clone());
+            available
+        }
+    };
+
+    Ok(locales
+        .into_iter()
+        .filter(|locale| available.contains(locale))
+        .collect())
+}
+
+fn parse_accept_language(value: &str) -> Vec<String> {
+    let mut locales: Vec<(String, f32)> = value
+        .split(',')
+        .filter_map(|entry| {
+            let mut parts = entry.split(';');
+            let locale = parts.next()?.trim();
+
+            let quality = parts
+                .filter_map(|param| param.trim().split_once('='))
+                .find_map(|(name, value)| name.eq_ignore_ascii_case("q").then_some(value))
+                .map_or(Some(1.0), |quality| quality.parse().ok())
+                .filter(|quality: &f32| (0.0..=1.0).contains(quality))?;
+
+            if locale == "*" || quality <= 0.0 {
+                return None;
+            }
+
+            Some((normalize_locale(locale)?, quality))
+        })
+        .collect();
+
+    // Stable, so locales with the same quality keep their order
+    locales.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+
+    locales.into_iter().map(|(locale, _)| locale).collect()
+}
+
+#[derive(sqlx::FromRow)]
+struct Translation {
+    badge_id: i64,
+    locale: String,
+    tooltip: String,
+}
+
+/// Replaces the tooltips of the badges with their translation into the most preferred locale that
+/// has one. Badges without any translation keep their default tooltip.
+///
+/// Translations of a badge take precedence over the translations of its definition. Definition
+/// translations are not used for badges that override the tooltip of their definition.
+pub async fn localize_badges(
+    db: &SqlitePool,
+    badges: &mut [Badge],
+    locales: &[String],
+) -> Result<(), Error> {
+    if badges.is_empty() || locales.is_empty() {
+        return Ok(());
+    }
+
+    let mut translations = Vec::new();
+    for chunk in badges.chunks(MAX_BADGES_PER_QUERY) {
+        let mut query = QueryBuilder::<Sqlite>::new(
+            "SELECT badge_id, locale, tooltip, 0 AS priority
+            FROM badge_tooltip_translations
+            WHERE locale IN ",
+        );
+        push_binds(&mut query, locales.iter());
+        query.push(" AND badge_id IN ");
+        push_binds(&mut query, chunk.iter().map(|badge| badge.id));
+        query.push(
+            " UNION ALL
+            SELECT badges.id AS badge_id, translations.locale, translations.tooltip, 1 AS priority
+            FROM badges
+            JOIN badge_definition_tooltip_translations AS translations
+                ON translations.definition_id = badges.definition_id
+            WHERE badges.tooltip IS NULL AND translations.locale IN ",
+        );
+        push_binds(&mut query, locales.iter());
+        query.push(" AND badges.id IN ");
+        push_binds(&mut query, chunk.iter().map(|badge| badge.id));
+        query.push(" ORDER BY priority");
+
+        translations.extend(query.build_query_as::<Translation>().fetch_all(db).await?);
+    }
+
+    // The translation for the most preferred locale wins, then the one with the lowest priority
+    let mut best = HashMap::<i64, (usize, String)>::new();
+    for translation in translations {
+        let Some(rank) = locales
+            .iter()
+            .position(|locale| *locale == translation.locale)
+        else {
+            continue;
+        };
+
+        match best.get(&translation.badge_id) {
+            Some((best_rank, _)) if *best_rank <= rank => {}
+            _ => {
+                best.insert(translation.badge_id, (rank, translation.tooltip));
+            }
+        }
+    }
+
+    for badge in badges {
+        if let Some((_, tooltip)) = best.remove(&badge.id) {
+            badge.tooltip = tooltip;
+        }
+    }
+
+    Ok(())
+}
+
+fn push_binds<'a, T>(query: &mut QueryBuilder<'a, Sqlite>, values: impl Iterator<Item = T>)
+where
+    T: 'a + sqlx::Encode<'a, Sqlite> + sqlx::Type<Sqlite> + Send,
+{
+    query.push("(");
+    let mut separated = query.separated(", ");
+    for value in values {
+        separated.push_bind(value);
+    }
+    separated.push_unseparated(")");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalizes_locales() {
+        let cases = [
+            ("en", Some("en")),
+            ("en-US", Some("en-us")),
+            (" zh-Hant-TW ", Some("zh-hant-tw")),
+            ("", None),
+            ("en_US", None),
+            ("en--us", None),
+            ("-en", None),
+            ("en-", None),
+            ("*", None),
+            ("en us", None),
+        ];
+
+        for (input, expected) in cases {
+            assert_eq!(normalize_locale(input).as_deref(), expected, "{input:?}");
+        }
+    }
+
+    #[test]
+    fn parses_accept_language() {
+        let cases: [(&str, &[&str]); 16] = [
+            ("", &[]),
+            ("de", &["de"]),
+            ("de-DE, en;q=0.5", &["de-de", "en"]),
+            // Sorted by quality, keeping the order of equal qualities
+            ("en;q=0.5, fr, de;q=0.8, it", &["fr", "it", "de", "en"]),
+            ("en;q=0.5, fr;q=0.5", &["en", "fr"]),
+            // Wildcards and unacceptable locales are skipped
+            ("*, de;q=0.5", &["de"]),
+            ("de;q=0, fr", &["fr"]),
+            ("de;q=0.000", &[]),
+            // Malformed qualities and locales skip their entry
+            ("de;q=high, fr;q=0.5", &["fr"]),
+            ("de;q=NaN, fr;q=0.5", &["fr"]),
+            ("de;q=inf, fr;q=0.5", &["fr"]),
+            ("de;q=1.5, fr;q=0.5", &["fr"]),
+            ("de;q=-0.5, fr;q=0.5", &["fr"]),
+            ("de_DE, fr", &["fr"]),
+            // Parameters are case insensitive and may come in any order
+            ("de;Q=0.2, fr;level=1;q=0.5", &["fr", "de"]),
+            (" de ; q=0.5 ,, fr ", &["fr", "de"]),
+        ];
+
+        for (input, expected) in cases {
+            assert_eq!(parse_accept_language(input), expected, "{input:?}");
+        }
+    }
+
+    #[test]
+    fn prefers_the_locale_parameter() {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ACCEPT_LANGUAGE, "fr".parse().unwrap());
+
+        assert_eq!(preferred_locales(Some("de-AT"), &headers), ["de-at", "de"]);
+        assert_eq!(preferred_locales(Some("not valid"), &headers), [""; 0]);
+        assert_eq!(preferred_locales(None, &headers), ["fr"]);
+        assert_eq!(preferred_locales(None, &HeaderMap::new()), [""; 0]);
+    }
+
+    #[test]
+    fn adds_base_languages_to_preferred_locales() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::ACCEPT_LANGUAGE,
+            "en-GB, en-US;q=0.9, de-DE;q=0.8, fr;q=0.7".parse().unwrap(),
+        );
+
+        // At most three requested locales, each followed by its base language once
+        assert_eq!(
+            preferred_locales(None, &headers),
+            ["en-gb", "en", "en-us", "de-de", "de"]
+        );
+    }
+}
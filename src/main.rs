@@ -1,7 +1,7 @@
 use api::{socket::VirtualChannels, AppState};
 use axum::{
     extract::{MatchedPath, Request},
-    routing::get,
+    routing::{delete, get, patch, post, put},
     Router,
 };
 use socketioxide::{handler::ConnectHandler, SocketIo};
@@ -29,8 +29,11 @@ async fn main() {
         .await
         .expect("Failed to run migrations");
 
+    api::jobs::spawn_all(&state);
+
     let (io_layer, io) = SocketIo::builder()
         .with_state(VirtualChannels::default())
+        .with_state(state.clone())
         .build_layer();
 
     let io_layer = ServiceBuilder::new()
@@ -45,7 +48,12 @@ async fn main() {
 
     let app = Router::new()
         .route("/health", get(|| async { "ok" }))
+        .route("/metrics", get(api::controllers::metrics::metrics))
         .route("/v2/auth/login", get(api::controllers::auth::login))
+        .route(
+            "/v2/audit-log",
+            get(api::controllers::audit_log::list_audit_log),
+        )
         .route(
             "/v2/bans",
             get(api::controllers::bans::list_bans).post(api::controllers::bans::create_ban),
@@ -56,13 +64,136 @@ async fn main() {
         )
         .route(
             "/v2/badges/{id}",
-            get(api::controllers::badges::get_badges_for_user)
+            get(api::controllers::badges::get_badge)
+                .patch(api::controllers::badges::update_badge)
                 .delete(api::controllers::badges::delete_badge),
         )
+        .route(
+            "/v2/badges/definitions",
+            get(api::controllers::badge_definitions::list_badge_definitions)
+                .post(api::controllers::badge_definitions::create_badge_definition),
+        )
+        .route(
+            "/v2/badges/definitions/{id}",
+            patch(api::controllers::badge_definitions::update_badge_definition)
+                .delete(api::controllers::badge_definitions::delete_badge_definition),
+        )
+        .route(
+            "/v2/badges/definitions/{id}/translations",
+            get(api::controllers::tooltip_translations::list_badge_definition_translations),
+        )
+        .route(
+            "/v2/badges/definitions/{id}/translations/{locale}",
+            put(api::controllers::tooltip_translations::set_badge_definition_translation).delete(
+                api::controllers::tooltip_translations::delete_badge_definition_translation,
+            ),
+        )
+        .route(
+            "/v2/badges/{id}/translations",
+            get(api::controllers::tooltip_translations::list_badge_translations),
+        )
+        .route(
+            "/v2/badges/{id}/translations/{locale}",
+            put(api::controllers::tooltip_translations::set_badge_translation)
+                .delete(api::controllers::tooltip_translations::delete_badge_translation),
+        )
+        .route(
+            "/v2/badges/claim-codes",
+            get(api::controllers::claim_codes::list_claim_codes)
+                .post(api::controllers::claim_codes::create_claim_code),
+        )
+        .route(
+            "/v2/badges/claim-codes/{code}",
+            delete(api::controllers::claim_codes::delete_claim_code),
+        )
+        .route(
+            "/v2/badges/redeem",
+            post(api::controllers::claim_codes::redeem_claim_code),
+        )
+        .route(
+            "/v2/badges/customizations",
+            get(api::controllers::badge_customizations::list_badge_customizations),
+        )
+        .route(
+            "/v2/badges/customizations/{id}/approve",
+            post(api::controllers::badge_customizations::approve_badge_customization),
+        )
+        .route(
+            "/v2/badges/customizations/{id}/reject",
+            post(api::controllers::badge_customizations::reject_badge_customization),
+        )
+        .route(
+            "/v2/badges/{id}/customization",
+            post(api::controllers::badge_customizations::customize_badge),
+        )
+        .route(
+            "/v2/badges/bulk",
+            post(api::controllers::badge_bulk::bulk_badges),
+        )
+        .route(
+            "/v2/badges/changes",
+            get(api::controllers::badges::list_badge_changes),
+        )
+        .route(
+            "/v2/badges/{id}/image",
+            get(api::controllers::badges::get_badge_image)
+                .post(api::controllers::badges::upload_badge_image),
+        )
         .route(
             "/v2/badges",
             get(api::controllers::badges::list_badges).post(api::controllers::badges::create_badge),
         )
+        .route(
+            "/v2/badges/{id}/visibility",
+            put(api::controllers::badges::set_badge_visibility),
+        )
+        .route(
+            "/v2/users/{user_id}/badges/order",
+            put(api::controllers::badges::reorder_badges),
+        )
+        .route(
+            "/v2/users/{user_id}/badges",
+            get(api::controllers::badges::get_badges_for_user),
+        )
+        .route(
+            "/v2/contributors/links",
+            get(api::controllers::contributors::list_contributor_links),
+        )
+        .route(
+            "/v2/contributors/links/{login}",
+            put(api::controllers::contributors::set_contributor_link)
+                .delete(api::controllers::contributors::delete_contributor_link),
+        )
+        .route(
+            "/v2/contributors/sync",
+            post(api::controllers::contributors::sync_contributors),
+        )
+        .route(
+            "/v2/integrations/donations",
+            post(api::controllers::donations::receive_donation_event),
+        )
+        .route(
+            "/v2/themes",
+            get(api::controllers::themes::list_themes).post(api::controllers::themes::create_theme),
+        )
+        .route(
+            "/v2/themes/{id}",
+            get(api::controllers::themes::get_theme)
+                .patch(api::controllers::themes::update_theme)
+                .delete(api::controllers::themes::delete_theme),
+        )
+        .route(
+            "/v2/themes/{id}/versions",
+            get(api::controllers::theme_versions::list_theme_versions),
+        )
+        .route(
+            "/v2/themes/{id}/versions/{version}",
+            get(api::controllers::theme_versions::get_theme_version),
+        )
+        .route(
+            "/v2/users/lookup",
+            post(api::controllers::users::lookup_users),
+        )
         .layer(CorsLayer::permissive())
         .layer(
             TraceLayer::new_for_http()
@@ -1,14 +1,97 @@
+use std::str::FromStr;
+
+use bitmask_enum::bitmask;
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
 use sqlx::Row;
 
+use crate::css::ThemeMetadata;
+
 #[derive(sqlx::FromRow, Serialize, Deserialize)]
 pub struct Theme {
-    pub id: i32,
+    pub id: i64,
     pub name: String,
     pub description: Option<String>,
-    pub author: Option<String>,
+    /// The user who published the theme. Themes from before authors were users have none.
+    pub author_id: Option<String>,
+    /// The free text author of themes published before authors were users, if it is not a user.
+    pub legacy_author: Option<String>,
     pub css: String,
+    /// The URL of the stylesheet, pinned to the current version.
+    #[sqlx(skip)]
+    #[serde(default)]
+    pub css_url: String,
+    /// The metadata header of the stylesheet, if it has one.
+    #[sqlx(skip)]
+    #[serde(default)]
+    pub metadata: Option<ThemeMetadata>,
+    /// The current version, as a semantic version.
+    pub version: String,
+    /// The oldest client version the current version supports.
+    pub min_client_version: Option<String>,
+    pub created_at: chrono::DateTime<Utc>,
+    pub updated_at: chrono::DateTime<Utc>,
+}
+
+/// A theme without its stylesheet, as returned by theme listings.
+#[derive(sqlx::FromRow, Serialize, Deserialize)]
+pub struct ThemeSummary {
+    pub id: i64,
+    pub name: String,
+    pub description: Option<String>,
+    pub author_id: Option<String>,
+    pub version: String,
+    pub created_at: chrono::DateTime<Utc>,
+    pub updated_at: chrono::DateTime<Utc>,
+}
+
+/// An immutable release of a theme.
+#[derive(sqlx::FromRow, Serialize, Deserialize)]
+pub struct ThemeVersion {
+    pub theme_id: i64,
+    pub version: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub css: String,
+    /// The URL of the stylesheet of this version.
+    #[sqlx(skip)]
+    #[serde(default)]
+    pub css_url: String,
+    pub changelog: Option<String>,
+    pub min_client_version: Option<String>,
+    pub created_at: chrono::DateTime<Utc>,
+}
+
+/// A theme version without its stylesheet, as returned by version listings.
+#[derive(sqlx::FromRow, Serialize, Deserialize)]
+pub struct ThemeVersionSummary {
+    pub theme_id: i64,
+    pub version: String,
+    #[sqlx(skip)]
+    #[serde(default)]
+    pub css_url: String,
+    pub changelog: Option<String>,
+    pub min_client_version: Option<String>,
+    pub created_at: chrono::DateTime<Utc>,
+}
+
+/// What a ban restricts the user from doing. Scopes can be combined.
+#[bitmask(i64)]
+#[derive(Serialize, Deserialize)]
+#[serde(transparent)]
+pub enum BanScope {
+    /// Banned from everything, including logging in.
+    Global,
+    /// Banned from connecting to the socket namespaces.
+    Socket,
+    /// Banned from receiving new badges.
+    Badges,
+}
+
+impl Default for BanScope {
+    fn default() -> Self {
+        Self::Global
+    }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -17,6 +100,32 @@ pub struct Ban {
     pub created_at: chrono::DateTime<Utc>,
     pub reason: Option<String>,
     pub expires: Option<chrono::DateTime<Utc>>,
+    pub scope: BanScope,
+}
+
+impl Ban {
+    /// Fetches the ban of a user, if there is one that has not expired yet.
+    pub async fn find_active(
+        db: impl sqlx::SqliteExecutor<'_>,
+        user_id: &str,
+    ) -> sqlx::Result<Option<Ban>> {
+        let ban = sqlx::query_as::<_, Ban>("SELECT * FROM bans WHERE user_id = ?")
+            .bind(user_id)
+            .fetch_optional(db)
+            .await?;
+
+        Ok(ban.filter(|ban| ban.is_active()))
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.expires.is_none_or(|expires| expires > Utc::now())
+    }
+
+    /// Whether this ban prevents the user from doing anything covered by `scope`.
+    /// Global bans restrict every scope.
+    pub fn restricts(&self, scope: BanScope) -> bool {
+        self.scope.intersects(scope | BanScope::Global)
+    }
 }
 
 impl<'r> sqlx::FromRow<'r, sqlx::sqlite::SqliteRow> for Ban {
@@ -29,17 +138,128 @@ impl<'r> sqlx::FromRow<'r, sqlx::sqlite::SqliteRow> for Ban {
             created_at: chrono::DateTime::<Utc>::from_naive_utc_and_offset(created_at, Utc),
             reason: row.try_get("reason")?,
             expires: expires.map(|dt| chrono::DateTime::<Utc>::from_naive_utc_and_offset(dt, Utc)),
+            scope: BanScope::from(row.try_get::<i64, _>("scope")?),
         })
     }
 }
 
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, sqlx::Type, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(rename_all = "lowercase")]
+pub enum BadgeType {
+    #[default]
+    Donor,
+    Contributor,
+    Staff,
+    Event,
+    Custom,
+}
+
+impl FromStr for BadgeType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "donor" => Ok(Self::Donor),
+            "contributor" => Ok(Self::Contributor),
+            "staff" => Ok(Self::Staff),
+            "event" => Ok(Self::Event),
+            "custom" => Ok(Self::Custom),
+            _ => Err(format!("unknown badge type `{s}`")),
+        }
+    }
+}
+
+impl BadgeType {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Donor => "donor",
+            Self::Contributor => "contributor",
+            Self::Staff => "staff",
+            Self::Event => "event",
+            Self::Custom => "custom",
+        }
+    }
+}
+
+/// A badge shared by many users. Users are assigned a definition through a [`Badge`].
+#[derive(sqlx::FromRow, Serialize, Deserialize)]
+pub struct BadgeDefinition {
+    pub id: i64,
+    pub name: String,
+    pub badge: String,
+    pub tooltip: String,
+    pub badge_type: BadgeType,
+}
+
+/// A badge of a user, with the values of its definition applied, if it has one.
 #[derive(sqlx::FromRow, Serialize, Deserialize)]
 pub struct Badge {
     pub id: i64,
     pub user_id: String,
+    pub definition_id: Option<i64>,
     pub badge: String,
     pub tooltip: String,
-    pub badge_type: String,
+    pub badge_type: BadgeType,
+    pub granted_at: chrono::DateTime<Utc>,
+    pub expires_at: Option<chrono::DateTime<Utc>>,
+    pub position: i64,
+    pub hidden: bool,
+    #[serde(skip)]
+    pub created_revision: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, sqlx::Type, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(rename_all = "lowercase")]
+pub enum CustomizationStatus {
+    Pending,
+    Approved,
+    Rejected,
+}
+
+/// A change a badge holder requested to their own badge.
+#[derive(sqlx::FromRow, Serialize, Deserialize)]
+pub struct BadgeCustomization {
+    pub id: i64,
+    pub badge_id: i64,
+    pub user_id: String,
+    pub tooltip: Option<String>,
+    pub badge: Option<String>,
+    pub status: CustomizationStatus,
+    pub created_at: chrono::DateTime<Utc>,
+    pub reviewed_at: Option<chrono::DateTime<Utc>>,
+    pub reviewed_by: Option<String>,
+}
+
+/// A code that grants a badge definition to whoever redeems it.
+#[derive(sqlx::FromRow, Serialize, Deserialize)]
+pub struct ClaimCode {
+    pub code: String,
+    pub definition_id: i64,
+    pub max_uses: Option<i64>,
+    pub uses: i64,
+    pub expires_at: Option<chrono::DateTime<Utc>>,
+    pub created_at: chrono::DateTime<Utc>,
+    pub created_by: String,
+}
+
+/// Links an account on the Git forge to a Discord account, for the contributor badge sync.
+#[derive(sqlx::FromRow, Serialize, Deserialize)]
+pub struct ContributorLink {
+    pub login: String,
+    pub user_id: String,
+    pub created_at: chrono::DateTime<Utc>,
+}
+
+/// An administrative action recorded in the audit log.
+#[derive(sqlx::FromRow, Serialize, Deserialize)]
+pub struct AuditLogEntry {
+    pub id: i64,
+    pub actor_id: String,
+    pub action: String,
+    pub details: sqlx::types::Json<serde_json::Value>,
+    pub created_at: chrono::DateTime<Utc>,
 }
 
 #[derive(sqlx::FromRow, Serialize, Deserialize)]
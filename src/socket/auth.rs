@@ -1,9 +1,14 @@
 use std::sync::Arc;
 
+use anyhow::anyhow;
 use serde::{Deserialize, Serialize};
-use socketioxide::extract::{Data, SocketRef};
+use socketioxide::extract::{Data, SocketRef, State};
 
-use crate::auth::Claims;
+use crate::{
+    auth::Claims,
+    models::{Ban, BanScope},
+    AppState,
+};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SocketAuthData {
@@ -13,9 +18,17 @@ pub struct SocketAuthData {
 pub async fn authenticate_middleware(
     socket: SocketRef,
     Data(auth): Data<SocketAuthData>,
+    State(state): State<Arc<AppState>>,
 ) -> Result<(), anyhow::Error> {
     let claims = Arc::new(Claims::decode(&auth.token)?);
 
+    if Ban::find_active(&state.db, claims.user_id())
+        .await?
+        .is_some_and(|ban| ban.restricts(BanScope::Socket))
+    {
+        return Err(anyhow!(crate::Error::Banned));
+    }
+
     socket.extensions.insert(claims);
 
     Ok(())
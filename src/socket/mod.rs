@@ -35,9 +35,9 @@ impl From<(UserId, ChannelId)> for VirtualChannelId {
         }
     }
 }
-impl ToString for VirtualChannelId {
-    fn to_string(&self) -> String {
-        format!("{};{}", self.owner_id, self.channel_id)
+impl std::fmt::Display for VirtualChannelId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{};{}", self.owner_id, self.channel_id)
     }
 }
 
@@ -84,8 +84,8 @@ impl VirtualChannels {
         let ids_to_remove: Vec<VirtualChannelId> = {
             let channel_map_guard = self.0.read().unwrap();
             channel_map_guard
-                .iter()
-                .filter_map(|(id, _)| {
+                .keys()
+                .filter_map(|id| {
                     if &id.owner_id == owner_id {
                         Some(id.clone()) // Clone the key to avoid borrowing issues
                     } else {
@@ -7,11 +7,7 @@ use std::{
 use anyhow::anyhow;
 use serde::{Deserialize, Serialize};
 use serenity::{
-    all::{
-        Channel, ChannelCreateEvent, ChannelId, ChannelUpdateEvent, GuildChannel,
-        MessageCreateEvent, MessageDeleteEvent, MessageUpdateEvent, ReactionAddEvent,
-        ReactionRemoveEvent, UserId,
-    },
+    all::{ChannelId, ChannelUpdateEvent, GuildChannel, UserId},
     json::{self, Value},
 };
 use socketioxide::extract::{AckSender, Data, Extension, SocketRef, State};
@@ -44,7 +40,7 @@ enum BroadcastEvent {
     MessageDelete(Value),
     ReactionAdd(Value),
     ReactionRemove(Value),
-    ChannelUpdate(ChannelUpdateEvent),
+    ChannelUpdate(Box<ChannelUpdateEvent>),
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -177,7 +173,7 @@ pub fn on_connect(
             ack.send(&Ack::Ok).ok();
 
             let Ok(channel_update_event) = json::to_value(virtual_channel.channel_data.clone())
-                .and_then(|v| json::from_value::<ChannelUpdateEvent>(v))
+                .and_then(json::from_value::<ChannelUpdateEvent>)
             else {
                 return;
             };
@@ -189,7 +185,7 @@ pub fn on_connect(
                     "broadcast_event_in_channel",
                     &BroadcastEventInChannel {
                         channel_id: virtual_channel_id.channel_id,
-                        event: BroadcastEvent::ChannelUpdate(channel_update_event),
+                        event: BroadcastEvent::ChannelUpdate(Box::new(channel_update_event)),
                     },
                 )
                 .ok();
@@ -0,0 +1,13 @@
+use serde::{Deserialize, Deserializer};
+
+/// Deserializes a present field into `Some`, even if its value is `null`.
+///
+/// Combined with `#[serde(default)]` on an `Option<Option<T>>`, this distinguishes a missing field
+/// (`None`) from a field explicitly set to `null` (`Some(None)`).
+pub fn deserialize_some<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    T::deserialize(deserializer).map(Some)
+}